@@ -1,13 +1,51 @@
-use crate::state::Subschema;
+use crate::state::{
+    Literals, ObjectProperty, StringFormat, Subschema, SubschemaArray, SubschemaTypes,
+    TaggedVariants,
+};
+use indexmap::IndexMap;
+use regex::Regex;
+use std::collections::HashSet;
 
+mod avro;
+mod definitions;
+mod go;
+mod graphql;
 mod jsonschema;
+mod model;
+mod naming;
+mod proto;
+mod python;
+mod rust;
+mod sql;
+mod typescript;
 
+pub use avro::Avro;
+pub use go::Go;
+pub use graphql::Graphql;
 pub use jsonschema::JsonSchema;
+pub use proto::Proto;
+pub use python::Python;
+pub use rust::Rust;
+pub use sql::{Sql, SqlDialect};
+pub use typescript::TypeScript;
 
 pub struct CodegenOptions {
     pub title: Option<String>,
     pub use_enum: bool,
     pub use_const: bool,
+    pub use_format: bool,
+    pub use_range: bool,
+    pub use_exclusive_minimum: bool,
+    pub use_length: bool,
+    pub use_items: bool,
+    pub use_tuples: bool,
+    pub use_map: bool,
+    pub map_threshold: usize,
+    pub use_variants: bool,
+    pub use_defs: bool,
+    pub use_property_defs: bool,
+    pub use_pydantic: bool,
+    pub sql_dialect: SqlDialect,
     pub enum_threshold: u8,
     pub enum_maximum: Option<u8>,
 }
@@ -18,6 +56,19 @@ impl CodegenOptions {
             title: None,
             use_enum: true,
             use_const: true,
+            use_format: true,
+            use_range: false,
+            use_exclusive_minimum: false,
+            use_length: false,
+            use_items: false,
+            use_tuples: true,
+            use_map: true,
+            map_threshold: 20,
+            use_variants: true,
+            use_defs: true,
+            use_property_defs: false,
+            use_pydantic: false,
+            sql_dialect: SqlDialect::Postgres,
             enum_threshold: 1,
             enum_maximum: None,
         }
@@ -27,3 +78,203 @@ impl CodegenOptions {
 pub trait Generation {
     fn generate(object: Subschema, options: CodegenOptions) -> String;
 }
+
+#[derive(Clone, Copy)]
+enum TypePrimative {
+    Null,
+    Boolean,
+    Integer, // Doesn't appear to be part of core but an accepted vocabulary
+    Number,
+    String,
+}
+
+impl TypePrimative {
+    fn from_literal(field: &Literals) -> Self {
+        match field {
+            Literals::Null => Self::Null,
+            Literals::Boolean(_) => Self::Boolean,
+            Literals::Integer(_) => Self::Integer,
+            Literals::Float(_) => Self::Number,
+            Literals::String(_) | Literals::Formatted(_, _) | Literals::Temporal(_, _) => {
+                Self::String
+            }
+        }
+    }
+
+    fn to_string(self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Boolean => "boolean",
+            Self::Integer => "integer",
+            Self::Number => "number",
+            Self::String => "string",
+        }
+    }
+}
+
+/// Kinds of value a subschema was observed holding
+fn subschema_kinds(sb: &Subschema) -> HashSet<&'static str> {
+    let mut kinds = sb
+        .types
+        .iter()
+        .flat_map(|t| t.values.iter())
+        .map(|v| TypePrimative::from_literal(v).to_string())
+        .collect::<HashSet<_>>();
+
+    if sb.array.is_some() {
+        kinds.insert("array");
+    }
+
+    if sb.object.is_some() {
+        kinds.insert("object");
+    }
+
+    kinds
+}
+
+/// Shared format of every non-null value, if they all have the same one
+fn common_format(values: &HashSet<Literals>) -> Option<StringFormat> {
+    let mut formats = values
+        .iter()
+        .filter(|v| **v != Literals::Null)
+        .map(|v| match v {
+            Literals::Formatted(f, _) => Some(*f),
+            _ => None,
+        });
+
+    let first = formats.next()??;
+
+    formats.all(|f| f == Some(first)).then_some(first)
+}
+
+/// Whether values are few, relative to how often they were seen, to be worth listing as an enum
+fn is_enum(types: &SubschemaTypes, options: &CodegenOptions) -> bool {
+    // If unique values are less than total count * ratio
+    let unique_threshold =
+        types.values.len() < (types.instance_count * options.enum_threshold as usize) / 100;
+
+    // If types are only boolean, skip enum
+    let only_bool = types
+        .values
+        .iter()
+        .all(|v| matches!(v, Literals::Boolean(_)));
+
+    // If unique values are below given maximum
+    let below_maximum = match options.enum_maximum {
+        Some(m) => types.values.len() < m.into(),
+        None => true,
+    };
+
+    options.use_enum && !types.overflowed && unique_threshold && !only_bool && below_maximum
+}
+
+/// Arrays seen more than once at one length, each position holding one kind, with differing kinds across positions
+fn is_tuple(positions: &[Subschema], instance_count: usize) -> bool {
+    if positions.len() < 2 || instance_count < 2 {
+        return false;
+    }
+
+    let kinds = positions
+        .iter()
+        .map(|p| {
+            let mut kinds = subschema_kinds(p);
+            kinds.remove("null");
+            kinds
+        })
+        .collect::<Vec<_>>();
+
+    kinds.iter().all(|k| k.len() == 1) && kinds.iter().any(|k| *k != kinds[0])
+}
+
+/// Folds the positions of every array not written as a tuple into its items, so only tuples
+/// keep a schema per position. Discriminators are chosen on the way, as folding merges objects
+fn fold_positions(sb: &mut Subschema, options: &CodegenOptions) {
+    if let Some(array) = sb.array.take() {
+        let tuple = array
+            .positions
+            .as_ref()
+            .is_some_and(|p| options.use_tuples && is_tuple(p, array.instance_count));
+
+        let mut array = if tuple {
+            array
+        } else {
+            SubschemaArray {
+                positions: None,
+                lengths: array.lengths,
+                unique: array.unique,
+                instance_count: array.instance_count,
+                items: Box::new(array.into_items()),
+            }
+        };
+
+        for p in array.positions.iter_mut().flatten() {
+            fold_positions(p, options);
+        }
+
+        fold_positions(&mut array.items, options);
+        sb.array = Some(array);
+    }
+
+    for (_, p) in sb.object.iter_mut().flatten() {
+        fold_positions(&mut p.value, options);
+    }
+
+    sb.choose_variants();
+
+    if let Some(TaggedVariants { values, .. }) = &mut sb.variants {
+        for variant in values.values_mut() {
+            fold_positions(variant, options);
+        }
+    }
+}
+
+/// Shapes of keys common to dictionary-like objects, as ECMA 262 patterns
+const MAP_KEY_PATTERNS: [&str; 4] = [
+    r"^[0-9]+$",
+    r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$",
+    r"^[0-9]{4}-[0-9]{2}-[0-9]{2}$",
+    r"^[A-Za-z]+[-_]?[0-9]+$",
+];
+
+enum MapKeys {
+    Pattern(&'static str),
+    Any,
+}
+
+/// Decides if an object is better described as a dictionary: many keys, all holding the same kind of
+/// value, where the keys either follow a common pattern or mostly come and go between instances
+fn detect_map(
+    properties: &IndexMap<String, ObjectProperty>,
+    object_count: usize,
+    options: &CodegenOptions,
+) -> Option<MapKeys> {
+    if !options.use_map || properties.len() < options.map_threshold {
+        return None;
+    }
+
+    // Objects seen again and again with the very same keys are rows of a table, as with
+    // columns named `col1` to `col20`
+    if object_count > 1 && properties.values().all(|p| p.required) {
+        return None;
+    }
+
+    let mut values = properties.values().map(|p| subschema_kinds(&p.value));
+    let first = values.next()?;
+
+    if !values.all(|k| k == first) {
+        return None;
+    }
+
+    let pattern = MAP_KEY_PATTERNS.into_iter().find(|p| {
+        let re = Regex::new(p).unwrap();
+        properties.keys().all(|k| re.is_match(k))
+    });
+
+    let required = properties.values().filter(|p| p.required).count();
+
+    match pattern {
+        Some(p) => Some(MapKeys::Pattern(p)),
+        None if required * 2 < properties.len() => Some(MapKeys::Any),
+        None => None,
+    }
+}
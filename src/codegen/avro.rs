@@ -0,0 +1,252 @@
+use super::{
+    CodegenOptions, Generation,
+    model::{Model, Record, Shape, Type},
+    naming::unique,
+};
+use crate::state::{StringFormat, Subschema};
+use regex::Regex;
+use serde_json::{Value, json, to_string_pretty};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::LazyLock,
+};
+
+static NAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap());
+
+/// Avro names only allow letters, digits and underscores, and can't start with a digit
+fn avro_name(key: &str) -> String {
+    let name = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+
+    if NAME.is_match(&name) {
+        name
+    } else {
+        format!("_{}", name)
+    }
+}
+
+/// Walks the model, writing out each named type in full where it's first used and by name after
+struct Schema<'a> {
+    records: HashMap<&'a str, &'a Record>,
+    /// Enum symbols, for those whose values are all usable as symbols
+    enums: HashMap<&'a str, &'a Vec<String>>,
+    defined: HashSet<String>,
+}
+
+impl Schema<'_> {
+    fn shape(&mut self, shape: &Shape) -> Value {
+        match shape {
+            // Nothing was seen to tell the type by
+            Shape::Any => json!("string"),
+            Shape::Boolean => json!("boolean"),
+            Shape::Integer => json!("long"),
+            Shape::Number => json!("double"),
+            Shape::String(Some(StringFormat::Uuid)) => {
+                json!({"type": "string", "logicalType": "uuid"})
+            }
+            Shape::String(_) => json!("string"),
+            Shape::Enum(name) => self.enumeration(name),
+            Shape::Array(t) => json!({"type": "array", "items": self.ty(t)}),
+            Shape::Tuple(ts) => {
+                let mut items: Vec<Value> = Vec::new();
+
+                for t in ts {
+                    for v in self.members(t) {
+                        if !items.contains(&v) {
+                            items.push(v);
+                        }
+                    }
+                }
+
+                let items = match items.len() {
+                    1 => items.pop().unwrap(),
+                    _ => Value::Array(items),
+                };
+
+                json!({"type": "array", "items": items})
+            }
+            Shape::Map(t) => json!({"type": "map", "values": self.ty(t)}),
+            Shape::Record(name) => self.record(name),
+            Shape::Union(shapes) => Value::Array(shapes.iter().map(|s| self.shape(s)).collect()),
+        }
+    }
+
+    /// Types of a union a value could be, as unions can't hold other unions
+    fn members(&mut self, ty: &Type) -> Vec<Value> {
+        let mut members = Vec::new();
+
+        if ty.nullable {
+            members.push(json!("null"));
+        }
+
+        match self.shape(&ty.shape) {
+            // Nothing but null was seen
+            _ if ty.nullable && ty.shape == Shape::Any => {}
+            Value::Array(union) => members.extend(union),
+            v => members.push(v),
+        }
+
+        members
+    }
+
+    fn ty(&mut self, ty: &Type) -> Value {
+        let mut members = self.members(ty);
+
+        match members.len() {
+            1 => members.pop().unwrap(),
+            _ => Value::Array(members),
+        }
+    }
+
+    fn enumeration(&mut self, name: &str) -> Value {
+        let Some(symbols) = self.enums.get(name).cloned() else {
+            return json!("string");
+        };
+
+        if !self.defined.insert(name.to_owned()) {
+            return json!(name);
+        }
+
+        json!({"type": "enum", "name": name, "symbols": symbols})
+    }
+
+    fn record(&mut self, name: &str) -> Value {
+        if !self.defined.insert(name.to_owned()) {
+            return json!(name);
+        }
+
+        let record = self.records[name];
+        let mut names: HashSet<String> = HashSet::new();
+
+        let fields = record
+            .fields
+            .iter()
+            .map(|f| {
+                let name = unique(avro_name(&f.key), |n| names.contains(n));
+                names.insert(name.clone());
+
+                let optional = Type {
+                    shape: f.value.shape.clone(),
+                    nullable: f.value.nullable || !f.required,
+                };
+
+                let mut field = json!({"name": name, "type": self.ty(&optional)});
+
+                // Defaults have to match the first type of a union, here always null
+                if optional.nullable {
+                    field["default"] = Value::Null;
+                }
+
+                field
+            })
+            .collect::<Vec<_>>();
+
+        json!({"type": "record", "name": name, "fields": fields})
+    }
+}
+
+pub struct Avro {}
+
+impl Generation for Avro {
+    fn generate(sb: Subschema, options: CodegenOptions) -> String {
+        let model = Model::new(sb, &options, &[]);
+
+        let mut schema = Schema {
+            records: model.records.iter().map(|r| (r.name.as_str(), r)).collect(),
+            enums: model
+                .enums
+                .iter()
+                .filter(|e| e.values.iter().all(|v| NAME.is_match(v)))
+                .map(|e| (e.name.as_str(), &e.values))
+                .collect(),
+            defined: HashSet::new(),
+        };
+
+        let values = schema.ty(&model.root);
+
+        to_string_pretty(&values)
+            .unwrap_or_else(|_| panic!("Values unable to be printed {:?}", values))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Avro;
+    use crate::codegen::{CodegenOptions, Generation};
+    use crate::state::{DataValues, Subschema};
+    use serde_json::{Value, json};
+
+    fn generate(value: Value, title: Option<&str>) -> Value {
+        let mut options = CodegenOptions::new();
+        options.title = title.map(String::from);
+        options.enum_threshold = 75;
+
+        let code = Avro::generate(Subschema::from_data(DataValues::from(value)), options);
+        serde_json::from_str(&code).unwrap()
+    }
+
+    #[test]
+    fn records() {
+        let schema = generate(
+            json!([
+                {"id": 1, "state": "ON", "user-name": "a", "tags": {"a": 1}, "home": {"x": 5, "y": 1}},
+                {"id": 2, "state": "ON", "user-name": null, "tags": {"b": 2}, "home": {"x": 2, "y": 1}},
+                {"id": 3, "state": "OFF", "user-name": "b", "tags": {}, "home": {"x": 3, "y": 1}, "work": {"x": 4, "y": 1}},
+                {"id": 4, "state": "ON", "user-name": "c", "tags": {}, "home": {"x": 1, "y": 1}}
+            ]),
+            Some("devices"),
+        );
+
+        assert_eq!(
+            schema,
+            json!({
+                "type": "array",
+                "items": {
+                    "type": "record",
+                    "name": "Device",
+                    "fields": [
+                        {"name": "id", "type": "long"},
+                        {"name": "state", "type": {"type": "enum", "name": "State", "symbols": ["OFF", "ON"]}},
+                        {"name": "user_name", "type": ["null", "string"], "default": null},
+                        {"name": "tags", "type": {
+                            "type": "record",
+                            "name": "Tags",
+                            "fields": [
+                                {"name": "a", "type": ["null", "long"], "default": null},
+                                {"name": "b", "type": ["null", "long"], "default": null}
+                            ]
+                        }},
+                        {"name": "home", "type": {
+                            "type": "record",
+                            "name": "XY",
+                            "fields": [{"name": "x", "type": "long"}, {"name": "y", "type": "long"}]
+                        }},
+                        {"name": "work", "type": ["null", "XY"], "default": null}
+                    ]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn recursion() {
+        let schema = generate(
+            json!({"value": 1, "next": {"value": 2, "next": {"value": 3, "next": null}}}),
+            Some("node"),
+        );
+
+        assert_eq!(
+            schema,
+            json!({
+                "type": "record",
+                "name": "Node",
+                "fields": [
+                    {"name": "value", "type": "long"},
+                    {"name": "next", "type": ["null", "Node"], "default": null}
+                ]
+            })
+        );
+    }
+}
@@ -0,0 +1,746 @@
+use super::{TypePrimative, common_format, subschema_kinds};
+use crate::state::{ObjectProperty, Subschema, TaggedVariants, crunch_schemas};
+use indexmap::IndexMap;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    mem,
+};
+
+/// Schemas hoisted out of the tree by name, stood in for where `References` point to them
+pub type Definitions = Vec<(String, Subschema)>;
+
+/// Name of the definition standing in for the object part of the subschema at each path
+pub type References = HashMap<Path, String>;
+
+pub const ROOT_NAME: &str = "root";
+
+// Fewest properties an object needs before repeats of it are shared
+const SHARED_MIN_PROPERTIES: usize = 2;
+
+// Most property keys used to name a shared definition
+const SHARED_NAME_KEYS: usize = 3;
+
+/// Step from a subschema to one nested in it
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Step {
+    Items,
+    Position(usize),
+    Property(String),
+    /// Values of an object written as a map, all properties folded together
+    Values,
+    Variant(String),
+}
+
+impl Step {
+    /// Leads into the object part of a subschema, which moves along with it into a definition
+    fn is_object(&self) -> bool {
+        matches!(self, Self::Property(_) | Self::Values | Self::Variant(_))
+    }
+}
+
+/// Where a subschema sits, as the steps down from the root schema or from a definition
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Path {
+    definition: Option<String>,
+    steps: Vec<Step>,
+}
+
+impl Path {
+    pub fn root() -> Self {
+        Self {
+            definition: None,
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn definition(name: &str) -> Self {
+        Self {
+            definition: Some(name.to_owned()),
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn join(&self, step: Step) -> Self {
+        let mut path = self.clone();
+        path.steps.push(step);
+        path
+    }
+
+    /// Steps left below `ancestor`, if this path is under it
+    fn below(&self, ancestor: &Self) -> Option<&[Step]> {
+        (self.definition == ancestor.definition && self.steps.len() > ancestor.steps.len())
+            .then(|| self.steps.split_at(ancestor.steps.len()))
+            .filter(|(head, _)| *head == ancestor.steps.as_slice())
+            .map(|(_, rest)| rest)
+    }
+}
+
+/// Moves the references found under the part of a subschema at `from` to `to`, as the subschema
+/// moves, or drops them when `to` is `None`
+fn move_references(
+    references: &mut References,
+    from: &Path,
+    to: Option<&Path>,
+    part: impl Fn(&Step) -> bool,
+) {
+    let moved = references
+        .keys()
+        .filter(|k| k.below(from).is_some_and(|rest| part(&rest[0])))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    for key in moved {
+        let name = references.remove(&key).unwrap();
+
+        if let Some(to) = to {
+            let mut path = to.clone();
+            path.steps.extend_from_slice(key.below(from).unwrap());
+            references.entry(path).or_insert(name);
+        }
+    }
+}
+
+/// Moves the references found under each property of the object at `at` to its values, for
+/// an object written as a map, giving the path of the values
+pub fn fold_values<'a>(
+    references: &mut References,
+    at: &Path,
+    keys: impl Iterator<Item = &'a String>,
+) -> Path {
+    let values = at.join(Step::Values);
+
+    for key in keys {
+        let property = at.join(Step::Property(key.clone()));
+        move_references(references, &property, Some(&values), |_| true);
+
+        if let Some(name) = references.remove(&property) {
+            references.entry(values.clone()).or_insert(name);
+        }
+    }
+
+    values
+}
+
+/// Keys of an object and the kinds of value held by each, nulls aside
+struct Profile {
+    keys: HashMap<String, HashSet<&'static str>>,
+}
+
+impl Profile {
+    fn of(sb: &Subschema) -> Option<Self> {
+        let keys = sb
+            .object
+            .as_ref()?
+            .iter()
+            .map(|(k, p)| {
+                let mut kinds = subschema_kinds(&p.value);
+                kinds.remove("null");
+                (k.to_owned(), kinds)
+            })
+            .collect();
+
+        Some(Self { keys })
+    }
+}
+
+#[derive(PartialEq)]
+enum Fit {
+    /// Same keys holding the same kinds of value
+    Same,
+    /// Most of the keys, as with the leaves of a tree
+    Partial,
+    None,
+}
+
+fn fit(ancestor: &Profile, candidate: &Subschema) -> Fit {
+    let Some(candidate) = Profile::of(candidate) else {
+        return Fit::None;
+    };
+
+    let kinds_agree = candidate.keys.iter().all(|(k, kinds)| {
+        ancestor
+            .keys
+            .get(k)
+            .is_some_and(|a| kinds.is_subset(a) || a.is_subset(kinds))
+    });
+
+    if !kinds_agree || candidate.keys.is_empty() {
+        Fit::None
+    } else if candidate.keys.len() == ancestor.keys.len() {
+        Fit::Same
+    } else if candidate.keys.len() * 2 >= ancestor.keys.len() {
+        Fit::Partial
+    } else {
+        Fit::None
+    }
+}
+
+/// How a child subschema relates to its parent
+#[derive(Clone, Copy, PartialEq)]
+enum Relation {
+    Nested,
+    /// Variants repeat the parent object itself, split on a discriminator, so they are never
+    /// matched as nested objects, only looked inside
+    Variant,
+}
+
+impl Relation {
+    fn is_copy(self) -> bool {
+        self != Self::Nested
+    }
+}
+
+/// Nested subschemas, named after the property they're found under, with the step to each
+fn children_mut<'a>(
+    sb: &'a mut Subschema,
+    name: &'a str,
+) -> Vec<(&'a str, &'a mut Subschema, Relation, Step)> {
+    let mut children: Vec<(&str, &mut Subschema, Relation, Step)> = Vec::new();
+
+    if let Some(a) = &mut sb.array {
+        children.push((name, &mut a.items, Relation::Nested, Step::Items));
+
+        // Only tuples keep their positions, in place of items
+        for (i, p) in a.positions.iter_mut().flatten().enumerate() {
+            children.push((name, p, Relation::Nested, Step::Position(i)));
+        }
+    }
+
+    for (k, p) in sb.object.iter_mut().flatten() {
+        children.push((k, &mut p.value, Relation::Nested, Step::Property(k.clone())));
+    }
+
+    if let Some(TaggedVariants { values, .. }) = &mut sb.variants {
+        for (tag, variant) in values.iter_mut() {
+            children.push((name, variant, Relation::Variant, Step::Variant(tag.clone())));
+        }
+    }
+
+    children
+}
+
+fn contains_same(ancestor: &Profile, sb: &mut Subschema) -> bool {
+    children_mut(sb, ROOT_NAME)
+        .into_iter()
+        .any(|(_, child, relation, _)| {
+            (relation != Relation::Variant && fit(ancestor, child) == Fit::Same)
+                || contains_same(ancestor, child)
+        })
+}
+
+/// Moves the object part of a subschema out, leaving a reference to `name` in its place. The
+/// references found inside move along to the definition, unless the object is a copy to drop
+fn take_object(
+    sb: &mut Subschema,
+    name: &str,
+    at: &Path,
+    references: &mut References,
+    collect: bool,
+) -> Subschema {
+    let to = Path::definition(name);
+    move_references(references, at, collect.then_some(&to), Step::is_object);
+    references.insert(at.clone(), name.to_owned());
+
+    Subschema {
+        object: sb.object.take(),
+        object_count: mem::take(&mut sb.object_count),
+        variants: sb.variants.take(),
+        ..Subschema::new()
+    }
+}
+
+/// Replaces every nested object fitting the ancestor with a reference, collecting the objects
+/// that aren't copies
+fn extract(
+    ancestor: &Profile,
+    sb: &mut Subschema,
+    name: &str,
+    at: &Path,
+    found: &mut Vec<Subschema>,
+    references: &mut References,
+    collect: bool,
+) {
+    for (_, child, relation, step) in children_mut(sb, name) {
+        let at = at.join(step);
+        let collect = collect && !relation.is_copy();
+
+        if relation == Relation::Variant || fit(ancestor, child) == Fit::None {
+            extract(ancestor, child, name, &at, found, references, collect);
+            continue;
+        }
+
+        let mut object = take_object(child, name, &at, references, collect);
+
+        // Found objects are folded into the definition, so their insides sit under its root
+        if collect {
+            let root = Path::definition(name);
+            extract(
+                ancestor,
+                &mut object,
+                name,
+                &root,
+                found,
+                references,
+                collect,
+            );
+            found.push(object);
+        }
+    }
+}
+
+fn unique_name(name: &str, definitions: &Definitions) -> String {
+    let taken = |n: &str| definitions.iter().any(|(d, _)| d == n);
+
+    if !taken(name) {
+        return name.to_owned();
+    }
+
+    (2..)
+        .map(|i| format!("{}{}", name, i))
+        .find(|n| !taken(n))
+        .unwrap()
+}
+
+fn fold(
+    sb: &mut Subschema,
+    name: &str,
+    at: &Path,
+    definitions: &mut Definitions,
+    references: &mut References,
+    copy: bool,
+) {
+    if let Some(profile) = Profile::of(sb)
+        && contains_same(&profile, sb)
+    {
+        // Copies were already folded into a definition alongside the original
+        let existing = definitions
+            .iter()
+            .find(|(_, d)| copy && Profile::of(d).is_some_and(|p| fit(&p, sb) == Fit::Same));
+
+        if let Some((name, _)) = existing {
+            take_object(sb, &name.to_owned(), at, references, false);
+            return;
+        }
+
+        let name = unique_name(name, definitions);
+        let index = definitions.len();
+
+        // Claimed up front, so definitions found further down get other names
+        definitions.push((name.clone(), Subschema::new()));
+
+        let root = Path::definition(&name);
+        let mut object = take_object(sb, &name, at, references, true);
+        let mut found = Vec::new();
+        extract(
+            &profile,
+            &mut object,
+            &name,
+            &root,
+            &mut found,
+            references,
+            true,
+        );
+
+        let mut object = found.into_iter().fold(object, crunch_schemas);
+        fold_children(&mut object, &name, &root, definitions, references);
+
+        definitions[index].1 = object;
+    }
+
+    fold_children(sb, name, at, definitions, references);
+}
+
+fn fold_children(
+    sb: &mut Subschema,
+    name: &str,
+    at: &Path,
+    definitions: &mut Definitions,
+    references: &mut References,
+) {
+    for (key, child, relation, step) in children_mut(sb, name) {
+        let at = at.join(step);
+        fold(child, key, &at, definitions, references, relation.is_copy());
+    }
+}
+
+/// Turns objects nesting copies of themselves, like the nodes of a tree, into definitions that
+/// refer back to themselves. The tree is sampled to whatever depth the data had, the definition
+/// covers any depth.
+pub fn extract_recursion(
+    sb: &mut Subschema,
+    definitions: &mut Definitions,
+    references: &mut References,
+) {
+    fold(sb, ROOT_NAME, &Path::root(), definitions, references, false);
+}
+
+/// Moves the value of each top level property into a definition named after its key, as for
+/// the sheets of a workbook
+pub fn extract_properties(
+    sb: &mut Subschema,
+    definitions: &mut Definitions,
+    references: &mut References,
+) {
+    for (key, property) in sb.object.iter_mut().flatten() {
+        let name = unique_name(key, definitions);
+        let at = Path::root().join(Step::Property(key.clone()));
+
+        move_references(references, &at, Some(&Path::definition(&name)), |_| true);
+        references.insert(at, name.clone());
+
+        definitions.push((name, mem::replace(&mut property.value, Subschema::new())));
+    }
+}
+
+/// Structure of a subschema, ignoring the values observed. Sites sharing a signature fold
+/// together, their `const` and `enum` values merging
+fn signature(sb: &Subschema, at: &Path, references: &References) -> String {
+    let mut kinds = sb
+        .types
+        .iter()
+        .flat_map(|t| t.values.iter())
+        .map(|v| TypePrimative::from_literal(v).to_string())
+        .collect::<Vec<_>>();
+
+    kinds.sort();
+    kinds.dedup();
+
+    let mut signature = kinds.join("|");
+
+    if let Some(format) = sb.types.as_ref().and_then(|t| common_format(&t.values)) {
+        signature.push_str(&format!("({})", format.name()));
+    }
+
+    if let Some(a) = &sb.array {
+        let items = match &a.positions {
+            Some(p) => p
+                .iter()
+                .enumerate()
+                .map(|(i, p)| self::signature(p, &at.join(Step::Position(i)), references))
+                .collect::<Vec<_>>()
+                .join(","),
+            None => self::signature(&a.items, &at.join(Step::Items), references),
+        };
+
+        signature.push_str(&format!("[{}]", items));
+    }
+
+    if let Some(o) = &sb.object {
+        signature.push_str(&object_signature(o, at, references));
+    }
+
+    if let Some(r) = references.get(at) {
+        signature.push_str(&format!("${:?}", r));
+    }
+
+    signature
+}
+
+fn object_signature(
+    object: &IndexMap<String, ObjectProperty>,
+    at: &Path,
+    references: &References,
+) -> String {
+    let mut properties = object
+        .iter()
+        .map(|(k, p)| {
+            let optional = if p.required { "" } else { "?" };
+            let at = at.join(Step::Property(k.clone()));
+            format!(
+                "{:?}{}:{}",
+                k,
+                optional,
+                signature(&p.value, &at, references)
+            )
+        })
+        .collect::<Vec<_>>();
+
+    properties.sort();
+
+    format!("{{{}}}", properties.join(","))
+}
+
+fn shareable(sb: &Subschema, at: &Path, references: &References) -> Option<String> {
+    sb.object
+        .as_ref()
+        .filter(|o| o.len() >= SHARED_MIN_PROPERTIES)
+        .map(|o| object_signature(o, at, references))
+}
+
+fn count_objects(
+    sb: &mut Subschema,
+    at: &Path,
+    references: &References,
+    counts: &mut HashMap<String, (usize, String)>,
+) {
+    for (_, child, relation, step) in children_mut(sb, ROOT_NAME) {
+        if relation.is_copy() {
+            continue;
+        }
+
+        let at = at.join(step);
+
+        if let Some(signature) = shareable(child, &at, references) {
+            let (count, _) = counts.entry(signature).or_insert_with(|| {
+                let mut keys = child
+                    .object
+                    .iter()
+                    .flat_map(|o| o.keys())
+                    .collect::<Vec<_>>();
+                keys.sort();
+
+                let name = keys
+                    .into_iter()
+                    .take(SHARED_NAME_KEYS)
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join("_");
+
+                (0, name)
+            });
+
+            *count += 1;
+        }
+
+        count_objects(child, &at, references, counts);
+    }
+}
+
+/// Replaces nested objects having a shared signature with references, collecting those that
+/// aren't copies by definition name
+fn hoist(
+    sb: &mut Subschema,
+    at: &Path,
+    shared: &HashMap<String, String>,
+    found: &mut HashMap<String, Vec<Subschema>>,
+    references: &mut References,
+    collect: bool,
+) {
+    for (_, child, relation, step) in children_mut(sb, ROOT_NAME) {
+        let at = at.join(step);
+        let collect = collect && !relation.is_copy();
+        let signature = shareable(child, &at, references).filter(|_| relation != Relation::Variant);
+
+        let Some(name) = signature.and_then(|s| shared.get(&s)) else {
+            hoist(child, &at, shared, found, references, collect);
+            continue;
+        };
+
+        let mut object = take_object(child, name, &at, references, collect);
+
+        if collect {
+            let root = Path::definition(name);
+            hoist(&mut object, &root, shared, found, references, collect);
+            found.entry(name.to_owned()).or_default().push(object);
+        }
+    }
+}
+
+/// Moves objects repeated with the same structure in several places into shared definitions,
+/// named after their first few property keys
+pub fn extract_shared(
+    sb: &mut Subschema,
+    definitions: &mut Definitions,
+    references: &mut References,
+) {
+    let mut counts: HashMap<String, (usize, String)> = HashMap::new();
+
+    count_objects(sb, &Path::root(), references, &mut counts);
+
+    for (name, definition) in definitions.iter_mut() {
+        count_objects(definition, &Path::definition(name), references, &mut counts);
+    }
+
+    // Named in a fixed order, so repeated runs give the same names
+    let repeated = counts
+        .into_iter()
+        .filter(|(_, (count, _))| *count > 1)
+        .map(|(signature, (_, name))| (signature, name))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut shared: HashMap<String, String> = HashMap::new();
+
+    for (signature, name) in repeated {
+        let name = unique_name(&name, definitions);
+
+        definitions.push((name.clone(), Subschema::new()));
+        shared.insert(signature, name);
+    }
+
+    let mut found: HashMap<String, Vec<Subschema>> = HashMap::new();
+
+    hoist(sb, &Path::root(), &shared, &mut found, references, true);
+
+    for (name, definition) in definitions.iter_mut() {
+        let at = Path::definition(name);
+        hoist(definition, &at, &shared, &mut found, references, true);
+    }
+
+    for (name, definition) in definitions.iter_mut() {
+        if let Some(objects) = found.remove(name) {
+            *definition = objects
+                .into_iter()
+                .reduce(crunch_schemas)
+                .unwrap_or(Subschema::new());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{CodegenOptions, fold_positions};
+    use super::{Definitions, Path, References, Step, extract_recursion, extract_shared};
+    use crate::state::{DataValues, Literals, Subschema};
+    use serde_json::json;
+    use std::collections::HashSet;
+
+    /// Schema as code generation sees it, with positions folded away
+    fn data(json: serde_json::Value) -> Subschema {
+        let mut sb = Subschema::from_data(DataValues::from(json));
+        fold_positions(&mut sb, &CodegenOptions::new());
+        sb
+    }
+
+    fn property(at: Path, key: &str) -> Path {
+        at.join(Step::Property(key.to_owned()))
+    }
+
+    #[test]
+    fn recursion() {
+        let mut tree = data(json!({
+            "name": "a",
+            "children": [
+                {"name": "b", "children": []},
+                {"name": "c", "children": [{"name": "d"}]}
+            ]
+        }));
+
+        let mut definitions = Definitions::new();
+        let mut references = References::new();
+        extract_recursion(&mut tree, &mut definitions, &mut references);
+
+        assert_eq!(references.len(), 2);
+        assert_eq!(references[&Path::root()], "root");
+        assert_eq!(tree.object, None);
+        assert_eq!(definitions.len(), 1);
+
+        let (name, node) = &definitions[0];
+        assert_eq!(name, "root");
+
+        let object = node.object.as_ref().unwrap();
+        assert!(object["name"].required);
+        assert!(!object["children"].required);
+
+        let items = &object["children"].value.array.as_ref().unwrap().items;
+        let at = property(Path::definition("root"), "children").join(Step::Items);
+        assert_eq!(references[&at], "root");
+        assert_eq!(items.object, None);
+    }
+
+    #[test]
+    fn nested_recursion() {
+        let mut doc = data(json!({
+            "title": "post",
+            "comments": [
+                {"id": 1, "text": "x", "replies": [{"id": 2, "text": "y", "replies": []}]}
+            ]
+        }));
+
+        let mut definitions = Definitions::new();
+        let mut references = References::new();
+        extract_recursion(&mut doc, &mut definitions, &mut references);
+
+        assert_eq!(references.get(&Path::root()), None);
+        assert_eq!(definitions.len(), 1);
+        assert_eq!(definitions[0].0, "comments");
+
+        let comments = property(Path::root(), "comments").join(Step::Items);
+        assert_eq!(references[&comments], "comments");
+
+        let replies = property(Path::definition("comments"), "replies").join(Step::Items);
+        assert_eq!(references[&replies], "comments");
+    }
+
+    #[test]
+    fn no_recursion() {
+        for json in [
+            json!({"id": 1, "user": {"id": 2}}),
+            json!({"id": 1, "name": "a", "owner": {"id": 2, "name": "b"}}),
+            json!([{"a": 1, "b": 2}, {"a": 3, "b": 4}]),
+            json!({"a": {"x": 1}, "b": {"x": 2}}),
+            json!([{"type": "a", "id": 1}, {"type": "b", "id": 2, "ok": true}]),
+        ] {
+            let mut sb = data(json.clone());
+            let expected = sb.clone();
+            let mut definitions = Definitions::new();
+            let mut references = References::new();
+
+            extract_recursion(&mut sb, &mut definitions, &mut references);
+
+            assert!(definitions.is_empty(), "{} should not be recursive", json);
+            assert!(references.is_empty());
+            assert_eq!(sb, expected);
+        }
+    }
+
+    #[test]
+    fn shared() {
+        let mut doc = data(json!({
+            "billing": {"street": "a", "city": "b", "zip": 1},
+            "shipping": {"street": "a", "city": "b", "zip": 1},
+            "contacts": [{"name": "x", "home": {"street": "a", "city": "b", "zip": 1}}],
+            "origin": {"street": "c", "city": "d", "zip": 2},
+            "total": {"amount": 1.5, "currency": "EUR"},
+            "tax": {"amount": 1.5, "currency": "EUR"}
+        }));
+
+        let mut definitions = Definitions::new();
+        let mut references = References::new();
+        extract_shared(&mut doc, &mut definitions, &mut references);
+
+        assert_eq!(definitions.len(), 2);
+
+        let definition = |name: &str| &definitions.iter().find(|(n, _)| n == name).unwrap().1;
+        let zip = &definition("city_street_zip").object.as_ref().unwrap()["zip"].value;
+        let zip = zip.types.as_ref().unwrap();
+
+        // Values of every site folded together, whatever they were
+        assert_eq!(zip.instance_count, 4);
+        assert_eq!(
+            zip.values,
+            HashSet::from([Literals::Integer(1), Literals::Integer(2)])
+        );
+        assert!(definition("amount_currency").object.is_some());
+
+        for key in ["billing", "shipping", "origin"] {
+            assert_eq!(references[&property(Path::root(), key)], "city_street_zip");
+        }
+        for key in ["total", "tax"] {
+            assert_eq!(references[&property(Path::root(), key)], "amount_currency");
+        }
+
+        let contact = property(Path::root(), "contacts").join(Step::Items);
+        assert_eq!(references[&property(contact, "home")], "city_street_zip");
+        assert_eq!(references.len(), 6);
+    }
+
+    #[test]
+    fn shared_structure_only() {
+        // Same keys, differing required flags, kinds or formats, are different structures
+        let mut doc = data(json!({
+            "a": {"x": 1, "y": "s"},
+            "b": {"x": "t", "y": "s"},
+            "c": [{"x": 1, "y": "s"}, {"x": 2}],
+            "d": {"x": 1, "y": "2024-01-01"}
+        }));
+        let expected = doc.clone();
+
+        let mut definitions = Definitions::new();
+        let mut references = References::new();
+        extract_shared(&mut doc, &mut definitions, &mut references);
+
+        assert!(definitions.is_empty());
+        assert!(references.is_empty());
+        assert_eq!(doc, expected);
+    }
+}
@@ -0,0 +1,337 @@
+use super::{
+    CodegenOptions, Generation,
+    model::{Enumeration, Field, Model, Record, Shape, Type},
+    naming::{identifier, pascal_case, unique, words},
+};
+use crate::state::{StringFormat, Subschema};
+use std::collections::HashSet;
+
+/// Package name used when no title is set
+const DEFAULT_PACKAGE: &str = "schema";
+
+/// Words written in capitals in Go names, as golint suggests
+const INITIALISMS: [&str; 24] = [
+    "API", "ASCII", "CPU", "CSS", "DNS", "EOF", "HTML", "HTTP", "HTTPS", "ID", "IP", "JSON", "OS",
+    "RAM", "SQL", "SSH", "TCP", "TLS", "UDP", "UI", "URI", "URL", "UUID", "XML",
+];
+
+/// Words of a key in `PascalCase`, keeping initialisms in capitals
+fn go_case(key: &str) -> String {
+    words(key)
+        .iter()
+        .map(|w| {
+            let upper = w.to_uppercase();
+
+            if INITIALISMS.contains(&upper.as_str()) {
+                upper
+            } else {
+                pascal_case(w)
+            }
+        })
+        .collect()
+}
+
+fn exported(key: &str) -> String {
+    // Names have to start with a capital letter to be exported
+    match identifier(go_case(key), "Field") {
+        n if n.starts_with('_') => format!("X{}", &n[1..]),
+        n => n,
+    }
+}
+
+fn package_name(title: Option<&str>) -> String {
+    let name = title
+        .unwrap_or_default()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>();
+
+    match name.chars().next() {
+        Some(c) if c.is_ascii_alphabetic() => name,
+        _ => DEFAULT_PACKAGE.to_owned(),
+    }
+}
+
+/// Lays out rows of cells in aligned columns, as gofmt does
+fn align(rows: Vec<Vec<String>>) -> Vec<String> {
+    let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let widths = (0..columns)
+        .map(|c| {
+            rows.iter()
+                .filter(|r| r.len() > c + 1)
+                .map(|r| r[c].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+
+    rows.into_iter()
+        .map(|row| {
+            let last = row.len() - 1;
+
+            row.into_iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    if i == last {
+                        cell
+                    } else {
+                        format!("{:width$} ", cell, width = widths[i])
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect()
+}
+
+struct File<'a> {
+    model: &'a Model,
+    time: bool,
+}
+
+impl File<'_> {
+    fn shape(&mut self, shape: &Shape) -> String {
+        match shape {
+            Shape::Any | Shape::Union(_) => String::from("any"),
+            // Positions of differing kinds have no common element type
+            Shape::Tuple(_) => String::from("[]any"),
+            Shape::Boolean => String::from("bool"),
+            Shape::Integer => String::from("int64"),
+            Shape::Number => String::from("float64"),
+            Shape::String(Some(StringFormat::DateTime)) => {
+                self.time = true;
+                String::from("time.Time")
+            }
+            Shape::String(_) => String::from("string"),
+            Shape::Enum(name) | Shape::Record(name) => name.clone(),
+            Shape::Array(t) => format!("[]{}", self.ty(t)),
+            Shape::Map(t) => format!("map[string]{}", self.ty(t)),
+        }
+    }
+
+    fn ty(&mut self, ty: &Type) -> String {
+        let shape = self.shape(&ty.shape);
+
+        if ty.nullable && !nilable(&ty.shape) {
+            format!("*{}", shape)
+        } else {
+            shape
+        }
+    }
+
+    fn field(&mut self, field: &Field, name: String) -> Vec<String> {
+        // Absent structs can only be left out through a pointer, and records holding themselves
+        // have to be one to have a size
+        let pointer = match &field.value.shape {
+            Shape::Record(r) => {
+                field.value.nullable || !field.required || self.model.recursive.contains(r)
+            }
+            s => field.value.nullable && !nilable(s),
+        };
+
+        let shape = self.shape(&field.value.shape);
+        let shape = if pointer {
+            format!("*{}", shape)
+        } else {
+            shape
+        };
+
+        vec![name, shape, struct_tag(&field.key, field.required)]
+    }
+
+    fn record(&mut self, record: &Record) -> String {
+        let mut names: HashSet<String> = HashSet::new();
+
+        let rows = record
+            .fields
+            .iter()
+            .map(|f| {
+                let name = unique(exported(&f.key), |n| names.contains(n));
+                names.insert(name.clone());
+
+                self.field(f, name)
+            })
+            .collect::<Vec<_>>();
+
+        if rows.is_empty() {
+            return format!("type {} struct{{}}", record.name);
+        }
+
+        format!(
+            "type {} struct {{\n{}\n}}",
+            record.name,
+            align(rows)
+                .into_iter()
+                .map(|r| format!("\t{}", r))
+                .collect::<Vec<_>>()
+                .join("\n")
+        )
+    }
+}
+
+/// Tag naming the json key of a field. Raw string literals can't hold a backtick, so tags with
+/// one are written as an interpreted string literal instead
+fn struct_tag(key: &str, required: bool) -> String {
+    let omit = if required { "" } else { ",omitempty" };
+    let key = key.replace('\\', "\\\\").replace('"', "\\\"");
+    let tag = format!("json:\"{}{}\"", key, omit);
+
+    if tag.contains('`') {
+        serde_json::to_string(&tag).unwrap()
+    } else {
+        format!("`{}`", tag)
+    }
+}
+
+/// Shapes which already have a nil value, needing no pointer
+fn nilable(shape: &Shape) -> bool {
+    matches!(
+        shape,
+        Shape::Any | Shape::Union(_) | Shape::Tuple(_) | Shape::Array(_) | Shape::Map(_)
+    )
+}
+
+fn enum_to_go(enumeration: &Enumeration) -> String {
+    let mut names: HashSet<String> = HashSet::new();
+
+    let rows = enumeration
+        .values
+        .iter()
+        .map(|v| {
+            let name = match go_case(v) {
+                n if n.is_empty() => format!("{}Empty", enumeration.name),
+                n => format!("{}{}", enumeration.name, n),
+            };
+            let name = unique(name, |n| names.contains(n));
+            names.insert(name.clone());
+
+            vec![
+                name,
+                enumeration.name.clone(),
+                format!("= {}", serde_json::to_string(v).unwrap()),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        "type {} string\n\nconst (\n{}\n)",
+        enumeration.name,
+        align(rows)
+            .into_iter()
+            .map(|r| format!("\t{}", r))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )
+}
+
+pub struct Go {}
+
+impl Generation for Go {
+    fn generate(sb: Subschema, options: CodegenOptions) -> String {
+        let model = Model::new(sb, &options, &[]);
+        let mut file = File {
+            model: &model,
+            time: false,
+        };
+
+        let mut items: Vec<String> = Vec::new();
+
+        if !model.root_is_record() {
+            let root = file.ty(&model.root);
+            items.push(format!("type {} {}", model.name, root));
+        }
+
+        items.extend(model.records.iter().map(|r| file.record(r)));
+        items.extend(model.enums.iter().map(enum_to_go));
+
+        let mut header = vec![format!(
+            "package {}",
+            package_name(options.title.as_deref())
+        )];
+
+        if file.time {
+            header.push(String::from("import \"time\""));
+        }
+
+        header
+            .into_iter()
+            .chain(items)
+            .collect::<Vec<_>>()
+            .join("\n\n")
+            + "\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Go, exported, struct_tag};
+    use crate::codegen::{CodegenOptions, Generation};
+    use crate::state::{DataValues, Subschema};
+    use serde_json::json;
+
+    #[test]
+    fn names() {
+        assert_eq!(exported("userId"), "UserID");
+        assert_eq!(exported("html_url"), "HTMLURL");
+        assert_eq!(exported("2fa"), "X2fa");
+        assert_eq!(exported("name"), "Name");
+    }
+
+    #[test]
+    fn tags() {
+        assert_eq!(struct_tag("id", true), r#"`json:"id"`"#);
+        assert_eq!(struct_tag("a\"b", false), r#"`json:"a\"b,omitempty"`"#);
+        assert_eq!(struct_tag(r"a\b", true), r#"`json:"a\\b"`"#);
+        assert_eq!(struct_tag("a`b", true), r#""json:\"a`b\"""#);
+    }
+
+    #[test]
+    fn structs() {
+        let mut options = CodegenOptions::new();
+        options.title = Some(String::from("orders"));
+        options.enum_threshold = 75;
+
+        let data = json!([
+            {"id": 1, "status": "open", "note": null, "at": "2024-01-01T10:00:00Z", "items": [{"sku": "a"}]},
+            {"id": 2, "status": "open", "note": "x", "at": "2024-01-02T10:00:00Z", "items": []},
+            {"id": 3, "status": "shipped", "note": "y", "at": "2024-01-03T10:00:00Z", "items": [], "buyer": {"name": "b"}},
+            {"id": 4, "status": "open", "note": "z", "at": "2024-01-04T10:00:00Z", "items": []}
+        ]);
+
+        let code = Go::generate(Subschema::from_data(DataValues::from(data)), options);
+
+        assert_eq!(
+            code,
+            "package orders
+
+import \"time\"
+
+type Orders []Order
+
+type Order struct {
+\tID     int64     `json:\"id\"`
+\tStatus Status    `json:\"status\"`
+\tNote   *string   `json:\"note\"`
+\tAt     time.Time `json:\"at\"`
+\tItems  []Item    `json:\"items\"`
+\tBuyer  *Buyer    `json:\"buyer,omitempty\"`
+}
+
+type Item struct {
+\tSku string `json:\"sku\"`
+}
+
+type Buyer struct {
+\tName string `json:\"name\"`
+}
+
+type Status string
+
+const (
+\tStatusOpen    Status = \"open\"
+\tStatusShipped Status = \"shipped\"
+)
+"
+        );
+    }
+}
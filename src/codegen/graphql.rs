@@ -0,0 +1,259 @@
+use super::{
+    CodegenOptions, Generation,
+    model::{Enumeration, Field, Model, Record, Shape, Type},
+    naming::{identifier, pascal_case, unique},
+};
+use crate::state::Subschema;
+use regex::Regex;
+use std::{collections::HashSet, sync::LazyLock};
+
+/// Names of the built in scalars and root operations, which types can't take
+const RESERVED_TYPES: [&str; 9] = [
+    "Boolean",
+    "Float",
+    "ID",
+    "Int",
+    "JSON",
+    "Mutation",
+    "Query",
+    "String",
+    "Subscription",
+];
+
+/// Scalar declared for values GraphQL has no type for, such as unions and maps
+const JSON_SCALAR: &str = "JSON";
+
+/// Type wrapping the root when it isn't made of records, holding it in a single field
+const VALUE_FIELD: &str = "value";
+
+static NAME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").unwrap());
+
+/// Field names have to be plain identifiers, with a leading `__` kept for introspection
+fn field_name(key: &str) -> String {
+    if NAME.is_match(key) && !key.starts_with("__") {
+        return key.to_owned();
+    }
+
+    let name = pascal_case(key)
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>();
+
+    let mut chars = name.chars();
+    let name = match chars.next() {
+        Some(c) => c.to_lowercase().chain(chars).collect(),
+        None => name,
+    };
+
+    identifier(name, "field")
+}
+
+/// Enum values have to be names, and can't be any of the literals
+fn enum_value(value: &str) -> bool {
+    NAME.is_match(value) && !matches!(value, "true" | "false" | "null")
+}
+
+struct Schema<'a> {
+    model: &'a Model,
+    json: bool,
+}
+
+impl Schema<'_> {
+    /// Named type of a shape, without its list or non-null wrappers
+    fn named(&mut self, shape: &Shape) -> String {
+        let name = match shape {
+            Shape::Boolean => "Boolean",
+            Shape::Integer => "Int",
+            Shape::Number => "Float",
+            Shape::String(_) => "String",
+            Shape::Enum(name) if self.enums().any(|e| e.name == *name) => name,
+            Shape::Enum(_) => "String",
+            // Types have to have at least one field
+            Shape::Record(name) if self.record(name).is_some_and(|r| !r.fields.is_empty()) => name,
+            Shape::Any
+            | Shape::Union(_)
+            | Shape::Tuple(_)
+            | Shape::Map(_)
+            | Shape::Array(_)
+            | Shape::Record(_) => {
+                self.json = true;
+                JSON_SCALAR
+            }
+        };
+
+        name.to_owned()
+    }
+
+    fn ty(&mut self, ty: &Type, required: bool) -> String {
+        let inner = match &ty.shape {
+            Shape::Array(t) => format!("[{}]", self.ty(t, true)),
+            s => self.named(s),
+        };
+
+        // Nothing but null was seen
+        let null_only = ty.nullable && ty.shape == Shape::Any;
+
+        if required && !ty.nullable && !null_only {
+            format!("{}!", inner)
+        } else {
+            inner
+        }
+    }
+
+    fn enums(&self) -> impl Iterator<Item = &Enumeration> {
+        self.model
+            .enums
+            .iter()
+            .filter(|e| e.values.iter().all(|v| enum_value(v)))
+    }
+
+    fn record(&self, name: &str) -> Option<&Record> {
+        self.model.records.iter().find(|r| r.name == name)
+    }
+
+    fn fields(&mut self, fields: &[Field]) -> Vec<String> {
+        let mut names: HashSet<String> = HashSet::new();
+
+        fields
+            .iter()
+            .map(|f| {
+                let name = unique(field_name(&f.key), |n| names.contains(n));
+                names.insert(name.clone());
+
+                format!("  {}: {}", name, self.ty(&f.value, f.required))
+            })
+            .collect()
+    }
+
+    fn object(&mut self, name: &str, fields: &[Field]) -> String {
+        format!("type {} {{\n{}\n}}", name, self.fields(fields).join("\n"))
+    }
+}
+
+fn enum_to_graphql(enumeration: &Enumeration) -> String {
+    format!(
+        "enum {} {{\n{}\n}}",
+        enumeration.name,
+        enumeration
+            .values
+            .iter()
+            .map(|v| format!("  {}", v))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )
+}
+
+pub struct Graphql {}
+
+impl Generation for Graphql {
+    fn generate(sb: Subschema, options: CodegenOptions) -> String {
+        let model = Model::new(sb, &options, &RESERVED_TYPES);
+        let mut schema = Schema {
+            model: &model,
+            json: false,
+        };
+
+        let mut items: Vec<String> = Vec::new();
+
+        // Only object types can be named, other roots need one holding them
+        let rows = match &model.root.shape {
+            Shape::Record(_) => true,
+            Shape::Array(t) => matches!(t.shape, Shape::Record(_)),
+            _ => false,
+        };
+
+        if !rows {
+            let wrapper = [Field {
+                key: VALUE_FIELD.to_owned(),
+                value: model.root.clone(),
+                required: true,
+                lengths: None,
+            }];
+
+            items.push(schema.object(&model.name, &wrapper));
+        }
+
+        for record in &model.records {
+            if !record.fields.is_empty() {
+                items.push(schema.object(&record.name, &record.fields));
+            }
+        }
+
+        items.extend(schema.enums().map(enum_to_graphql).collect::<Vec<_>>());
+
+        if schema.json {
+            items.insert(0, format!("scalar {}", JSON_SCALAR));
+        }
+
+        items.join("\n\n") + "\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Graphql, field_name};
+    use crate::codegen::{CodegenOptions, Generation};
+    use crate::state::{DataValues, Subschema};
+    use serde_json::{Value, json};
+
+    fn generate(value: Value, title: Option<&str>) -> String {
+        let mut options = CodegenOptions::new();
+        options.title = title.map(String::from);
+        options.enum_threshold = 75;
+
+        Graphql::generate(Subschema::from_data(DataValues::from(value)), options)
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(field_name("user_id"), "user_id");
+        assert_eq!(field_name("user name"), "userName");
+        assert_eq!(field_name("2fa"), "_2fa");
+        assert_eq!(field_name("__typename"), "typename");
+    }
+
+    #[test]
+    fn types() {
+        let code = generate(
+            json!([
+                {"id": 1, "status": "OPEN", "score": 1.5, "tags": ["a"], "note": null, "meta": {"k": "v"}},
+                {"id": 2, "status": "OPEN", "score": 2, "tags": [], "note": "x", "meta": {"k": "w"}},
+                {"id": 3, "status": "CLOSED", "score": 3, "tags": [], "note": "y", "meta": {"k": "u"}},
+                {"id": 4, "status": "OPEN", "score": 4, "tags": [null], "note": "z", "extra": [1, "a"]}
+            ]),
+            Some("tickets"),
+        );
+
+        assert_eq!(
+            code,
+            r#"scalar JSON
+
+type Ticket {
+  id: Int!
+  status: Status!
+  score: Float!
+  tags: [String]!
+  note: String
+  meta: Meta
+  extra: [JSON!]
+}
+
+type Meta {
+  k: String!
+}
+
+enum Status {
+  CLOSED
+  OPEN
+}
+"#
+        );
+    }
+
+    #[test]
+    fn wrapped_root() {
+        let code = generate(json!(["a", "b"]), Some("names"));
+
+        assert_eq!(code, "type Names {\n  value: [String!]!\n}\n");
+    }
+}
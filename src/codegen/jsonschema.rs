@@ -1,54 +1,64 @@
-use super::{CodegenOptions, Generation};
-use crate::state::{Literals, ObjectProperty, Subschema, SubschemaTypes};
-use serde_json::{Number, Value, json, to_string_pretty};
-use std::{
-    cmp::Ordering,
-    collections::{HashMap, HashSet},
+use super::{
+    CodegenOptions, Generation, MapKeys, TypePrimative, common_format,
+    definitions::{self, Path, References, Step},
+    detect_map, fold_positions, is_enum, is_tuple,
 };
+use crate::state::{
+    LengthRange, Literals, ObjectProperty, Subschema, SubschemaArray, SubschemaTypes,
+    TaggedVariants, crunch_schemas,
+};
+use indexmap::IndexMap;
+use serde_json::{Map, Number, Value, json, to_string_pretty};
+use std::{cmp::Ordering, collections::HashSet};
 
 const SCHEMA_VERSION: &str = "https://json-schema.org/draft/2020-12/schema";
 
-#[derive(Clone, Copy)]
-enum TypePrimative {
-    Null,
-    Boolean,
-    Integer, // Doesn't appear to be part of core but an accepted vocabulary
-    Number,
-    String,
-}
-
-impl TypePrimative {
-    fn from_literal(field: &Literals) -> Self {
-        match field {
-            Literals::Null => Self::Null,
-            Literals::Boolean(_) => Self::Boolean,
-            Literals::Integer(_) => Self::Integer,
-            Literals::Float(_) => Self::Number,
-            Literals::String(_) => Self::String,
-        }
-    }
-
-    fn to_string(self) -> &'static str {
-        match self {
-            Self::Null => "null",
-            Self::Boolean => "boolean",
-            Self::Integer => "integer",
-            Self::Number => "number",
-            Self::String => "string",
-        }
-    }
-}
-
 fn literal_to_value(l: Literals) -> Value {
     match l {
         Literals::Null => Value::Null,
         Literals::Boolean(b) => Value::Bool(b),
         Literals::Integer(i) => Value::Number(i.into()),
         Literals::Float(f) => Value::from(Number::from_f64(f64::from_bits(f))),
-        Literals::String(s) => Value::String(s),
+        Literals::String(s) | Literals::Formatted(_, s) | Literals::Temporal(_, s) => {
+            Value::String(s)
+        }
     }
 }
 
+/// Bounds of the observed numeric values, as `minimum`/`maximum` keywords
+fn numeric_range(values: &HashSet<Literals>, options: &CodegenOptions) -> Map<String, Value> {
+    let mut numbers = values
+        .iter()
+        .filter_map(|v| match v {
+            Literals::Integer(i) => Some((*i as f64, Value::from(*i))),
+            Literals::Float(f) => Some((f64::from_bits(*f), literal_to_value(v.clone()))),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut range = Map::new();
+
+    if numbers.is_empty() {
+        return range;
+    }
+
+    numbers.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    let (lowest, minimum) = numbers.first().unwrap().clone();
+    let (_, maximum) = numbers.pop().unwrap();
+
+    // Values all above zero are taken as positive, in place of the lowest one observed
+    if options.use_exclusive_minimum && lowest > 0.0 {
+        range.insert(String::from("exclusiveMinimum"), json!(0));
+    } else {
+        range.insert(String::from("minimum"), minimum);
+    }
+
+    range.insert(String::from("maximum"), maximum);
+
+    range
+}
+
 fn literals_to_json(types: SubschemaTypes, options: &CodegenOptions) -> Value {
     let mut primatives = types
         .values
@@ -63,33 +73,208 @@ fn literals_to_json(types: SubschemaTypes, options: &CodegenOptions) -> Value {
         _ => panic!("(Unreachable) Collapsing subscheama types with no values"),
     };
 
-    // If unique values are less than total count * ratio
-    let unique_threshold =
-        types.values.len() < (types.instance_count * options.enum_threshold as usize) / 100;
+    let create_enum = is_enum(&types, options);
 
-    // If types are only boolean, skip enum
-    let only_bool = types
-        .values
-        .iter()
-        .all(|v| matches!(v, Literals::Boolean(_)));
-
-    // If unique values are below given maximum
-    let below_maximum = match options.enum_maximum {
-        Some(m) => types.values.len() < m.into(),
-        None => true,
-    };
+    let create_const = options.use_const && !types.overflowed && (types.values.len() == 1);
 
-    let create_enum = options.use_enum && unique_threshold && !only_bool && below_maximum;
+    let comment = types
+        .declared
+        .as_ref()
+        .map(|d| json!(format!("Declared as {}", d)));
 
-    let create_const = options.use_const && (types.values.len() == 1);
-
-    if create_const {
+    let mut schema = if create_const {
         json!({"const": literal_to_value(types.values.into_iter().next().unwrap())})
     } else if create_enum {
         json!({"enum": types.values.into_iter().map(literal_to_value).collect::<Vec<_>>()})
     } else {
-        json!({"type": type_part})
+        let mut schema = Map::new();
+
+        schema.insert(String::from("type"), type_part);
+
+        if let Some(format) = common_format(&types.values).filter(|_| options.use_format) {
+            schema.insert(String::from("format"), json!(format.name()));
+        }
+
+        if options.use_range {
+            schema.extend(numeric_range(&types.values, options));
+        }
+
+        if let Some(LengthRange { min, max }) = types.string_lengths.filter(|_| options.use_length)
+        {
+            schema.insert(String::from("minLength"), json!(min));
+            schema.insert(String::from("maxLength"), json!(max));
+        }
+
+        Value::Object(schema)
+    };
+
+    if let Some(comment) = comment {
+        schema["$comment"] = comment;
     }
+
+    schema
+}
+
+fn array_to_json(
+    array: SubschemaArray,
+    at: &Path,
+    references: &mut References,
+    options: &CodegenOptions,
+) -> Value {
+    let mut schema = Map::new();
+    let SubschemaArray {
+        lengths, unique, ..
+    } = array;
+
+    schema.insert(String::from("type"), json!("array"));
+
+    match array.positions {
+        Some(p) if options.use_tuples && is_tuple(&p, array.instance_count) => {
+            let prefix = p
+                .into_iter()
+                .enumerate()
+                .map(|(i, sb)| {
+                    subschema_to_json(sb, &at.join(Step::Position(i)), references, options)
+                })
+                .collect::<Vec<_>>();
+
+            schema.insert(String::from("prefixItems"), Value::Array(prefix));
+            schema.insert(String::from("items"), Value::Bool(false));
+        }
+        positions => {
+            let items = SubschemaArray { positions, ..array }.into_items();
+
+            schema.insert(
+                String::from("items"),
+                subschema_to_json(items, &at.join(Step::Items), references, options),
+            );
+        }
+    }
+
+    if options.use_items {
+        schema.insert(String::from("minItems"), json!(lengths.min));
+        schema.insert(String::from("maxItems"), json!(lengths.max));
+
+        // Arrays that never held two elements say nothing about uniqueness
+        if unique && lengths.max > 1 {
+            schema.insert(String::from("uniqueItems"), json!(true));
+        }
+    }
+
+    Value::Object(schema)
+}
+
+fn object_to_json(
+    o: IndexMap<String, ObjectProperty>,
+    count: usize,
+    at: &Path,
+    references: &mut References,
+    options: &CodegenOptions,
+) -> Value {
+    if let Some(keys) = detect_map(&o, count, options) {
+        let values = definitions::fold_values(references, at, o.keys());
+
+        let values = o
+            .into_values()
+            .map(|p| p.value)
+            .reduce(crunch_schemas)
+            .map(|v| subschema_to_json(v, &values, references, options))
+            .unwrap_or(json!({}));
+
+        return match keys {
+            MapKeys::Pattern(p) => json!({
+                "type": "object",
+                "patternProperties": {p: values},
+                "additionalProperties": false
+            }),
+            MapKeys::Any => json!({"type": "object", "additionalProperties": values}),
+        };
+    }
+
+    properties_to_json(o, at, references, options)
+}
+
+fn properties_to_json(
+    o: IndexMap<String, ObjectProperty>,
+    at: &Path,
+    references: &mut References,
+    options: &CodegenOptions,
+) -> Value {
+    let required = o
+        .iter()
+        .filter(|(_, ObjectProperty { value: _, required })| *required)
+        .map(|(k, _)| k.to_owned())
+        .collect::<Vec<_>>();
+
+    let properties = o
+        .into_iter()
+        .map(
+            |(
+                k,
+                ObjectProperty {
+                    value: v,
+                    required: _,
+                },
+            )| {
+                let schema =
+                    subschema_to_json(v, &at.join(Step::Property(k.clone())), references, options);
+                (k, schema)
+            },
+        )
+        .collect::<Map<_, _>>();
+
+    json!({"type": "object", "properties": properties, "required": required})
+}
+
+/// Keeps the discriminator when it splits objects into more than one shape
+fn select_discriminator(
+    variants: TaggedVariants,
+    options: &CodegenOptions,
+) -> Option<(String, IndexMap<String, Subschema>)> {
+    if !options.use_variants {
+        return None;
+    }
+
+    fn shape(sb: &Subschema) -> HashSet<&String> {
+        sb.object.iter().flat_map(|o| o.keys()).collect()
+    }
+
+    let mut shapes = variants.values.values().map(shape);
+    let first = shapes.next().unwrap_or_default();
+
+    shapes
+        .any(|s| s != first)
+        .then_some((variants.key, variants.values))
+}
+
+/// Variants are always records, as each carries the discriminator
+fn variants_to_json(
+    tag: String,
+    variants: IndexMap<String, Subschema>,
+    at: &Path,
+    references: &mut References,
+    options: &CodegenOptions,
+) -> Value {
+    let schemas = variants
+        .into_iter()
+        .map(|(value, sb)| {
+            let at = at.join(Step::Variant(value.clone()));
+
+            let mut schema = match references.get(&at) {
+                Some(name) => json!({"$ref": definition_ref(name)}),
+                None => properties_to_json(sb.object.unwrap_or_default(), &at, references, options),
+            };
+            schema["properties"][&tag] = json!({"const": value});
+            schema
+        })
+        .collect::<Vec<_>>();
+
+    json!({"oneOf": schemas})
+}
+
+/// Points into `$defs`, escaping the name as a JSON pointer token
+fn definition_ref(name: &str) -> String {
+    format!("#/$defs/{}", name.replace('~', "~0").replace('/', "~1"))
 }
 
 fn subschema_to_json(
@@ -97,7 +282,12 @@ fn subschema_to_json(
         types,
         array,
         object,
+        object_count,
+        variants,
+        ..
     }: Subschema,
+    at: &Path,
+    references: &mut References,
     options: &CodegenOptions,
 ) -> Value {
     let mut schemas: Vec<Value> = Vec::new();
@@ -110,31 +300,23 @@ fn subschema_to_json(
 
     // Array case
     if let Some(a) = array {
-        schemas.push(json!({"type": "array", "items": subschema_to_json(*a, options)}));
+        schemas.push(array_to_json(a, at, references, options));
     };
 
     // Object case
-    if let Some(o) = object {
-        let required = o
-            .iter()
-            .filter(|(_, ObjectProperty { value: _, required })| *required)
-            .map(|(k, _)| k.to_owned())
-            .collect::<Vec<_>>();
-
-        let properties = o
-            .into_iter()
-            .map(
-                |(
-                    k,
-                    ObjectProperty {
-                        value: v,
-                        required: _,
-                    },
-                )| (k, subschema_to_json(v, options)),
-            )
-            .collect::<HashMap<_, _>>();
-
-        schemas.push(json!({"type": "object", "properties": properties, "required": required}));
+    match (
+        object,
+        variants.and_then(|v| select_discriminator(v, options)),
+    ) {
+        (Some(_), Some((tag, variants))) => {
+            schemas.push(variants_to_json(tag, variants, at, references, options))
+        }
+        (Some(o), None) => schemas.push(object_to_json(o, object_count, at, references, options)),
+        _ => {}
+    };
+
+    if let Some(name) = references.get(at) {
+        schemas.push(json!({"$ref": definition_ref(name)}));
     };
 
     if schemas.is_empty() {
@@ -149,18 +331,62 @@ fn subschema_to_json(
 pub struct JsonSchema {}
 
 impl Generation for JsonSchema {
-    fn generate(sb: Subschema, options: CodegenOptions) -> String {
-        let values = subschema_to_json(sb, &options);
-        let values = if let Value::Object(mut o) = values {
-            let _ = o.insert(
+    fn generate(mut sb: Subschema, options: CodegenOptions) -> String {
+        let mut definitions = definitions::Definitions::new();
+        let mut references = References::new();
+
+        fold_positions(&mut sb, &options);
+
+        if options.use_property_defs {
+            definitions::extract_properties(&mut sb, &mut definitions, &mut references);
+        }
+
+        if options.use_defs {
+            definitions::extract_recursion(&mut sb, &mut definitions, &mut references);
+            definitions::extract_shared(&mut sb, &mut definitions, &mut references);
+        }
+
+        let values = match sb {
+            // Hoisted properties are bare references, alike to map detection, so keep their keys
+            Subschema {
+                types: None,
+                array: None,
+                object: Some(o),
+                ..
+            } if options.use_property_defs => {
+                properties_to_json(o, &Path::root(), &mut references, &options)
+            }
+            sb => subschema_to_json(sb, &Path::root(), &mut references, &options),
+        };
+        let values = if let Value::Object(o) = values {
+            // Document keywords lead, definitions trail the schema using them
+            let mut root = Map::new();
+
+            root.insert(
                 String::from("$schema"),
                 Value::String(String::from(SCHEMA_VERSION)),
             );
 
             if let Some(title) = &options.title {
-                let _ = o.insert(String::from("title"), Value::String(title.clone()));
+                root.insert(String::from("title"), Value::String(title.clone()));
             }
-            Value::Object(o)
+
+            root.extend(o);
+
+            if !definitions.is_empty() {
+                let defs = definitions
+                    .into_iter()
+                    .map(|(name, sb)| {
+                        let at = Path::definition(&name);
+                        let schema = subschema_to_json(sb, &at, &mut references, &options);
+                        (name, schema)
+                    })
+                    .collect::<Map<_, _>>();
+
+                root.insert(String::from("$defs"), Value::Object(defs));
+            }
+
+            Value::Object(root)
         } else {
             values
         };
@@ -169,3 +395,459 @@ impl Generation for JsonSchema {
             .unwrap_or_else(|_| panic!("Values unable to be printed {:?}", values))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CodegenOptions, JsonSchema, Path, References, fold_positions, literals_to_json};
+    use crate::codegen::Generation;
+    use crate::state::{
+        DataValues, LengthRange, Literals, StringFormat, Subschema, SubschemaTypes,
+    };
+    use serde_json::{Value, json};
+    use std::collections::HashSet;
+
+    fn subschema_to_json(sb: Subschema, options: &CodegenOptions) -> Value {
+        super::subschema_to_json(sb, &Path::root(), &mut References::new(), options)
+    }
+
+    fn types(values: &[Literals]) -> SubschemaTypes {
+        SubschemaTypes {
+            values: values.iter().cloned().collect::<HashSet<_>>(),
+            instance_count: values.len(),
+            string_lengths: None,
+            overflowed: false,
+            declared: None,
+        }
+    }
+
+    fn date(s: &str) -> Literals {
+        Literals::Formatted(StringFormat::Date, s.to_owned())
+    }
+
+    #[test]
+    fn formats() {
+        let options = CodegenOptions::new();
+
+        assert_eq!(
+            literals_to_json(types(&[date("2024-01-01"), date("2024-01-02")]), &options),
+            json!({"type": "string", "format": "date"})
+        );
+
+        assert_eq!(
+            literals_to_json(
+                types(&[date("2024-01-01"), date("2024-01-02"), Literals::Null]),
+                &options
+            )["format"],
+            json!("date")
+        );
+
+        let mixed = [
+            date("2024-01-01"),
+            Literals::Formatted(StringFormat::Time, String::from("10:00:00Z")),
+        ];
+        assert_eq!(
+            literals_to_json(types(&mixed), &options),
+            json!({"type": "string"})
+        );
+
+        // Dates not strictly RFC 3339 are temporal, but have no format
+        let loose = [
+            date("2024-01-01"),
+            Literals::Temporal(StringFormat::Date, String::from("01/02/2024")),
+        ];
+        assert_eq!(
+            literals_to_json(types(&loose), &options),
+            json!({"type": "string"})
+        );
+
+        let partial = [date("2024-01-01"), Literals::String(String::from("soon"))];
+        assert_eq!(
+            literals_to_json(types(&partial), &options),
+            json!({"type": "string"})
+        );
+
+        let emails = [
+            Literals::Formatted(StringFormat::Email, String::from("a@example.com")),
+            Literals::Formatted(StringFormat::Email, String::from("b@example.com")),
+        ];
+        assert_eq!(
+            literals_to_json(types(&emails), &options),
+            json!({"type": "string", "format": "email"})
+        );
+
+        let declared = SubschemaTypes {
+            declared: Some(String::from("DATE")),
+            ..types(&[date("2024-01-01"), date("2024-01-02")])
+        };
+        assert_eq!(
+            literals_to_json(declared, &options),
+            json!({"type": "string", "format": "date", "$comment": "Declared as DATE"})
+        );
+
+        let no_format = CodegenOptions {
+            use_format: false,
+            ..CodegenOptions::new()
+        };
+        assert_eq!(
+            literals_to_json(types(&emails), &no_format),
+            json!({"type": "string"})
+        );
+    }
+
+    #[test]
+    fn ranges() {
+        let ranged = CodegenOptions {
+            use_range: true,
+            use_enum: false,
+            ..CodegenOptions::new()
+        };
+
+        let integers = [
+            Literals::Integer(3),
+            Literals::Integer(-7),
+            Literals::Integer(12),
+        ];
+        assert_eq!(
+            literals_to_json(types(&integers), &ranged),
+            json!({"type": "integer", "minimum": -7, "maximum": 12})
+        );
+
+        let mixed = [
+            Literals::Integer(3),
+            Literals::Float((0.5_f64).to_bits()),
+            Literals::Null,
+            Literals::String(String::from("n/a")),
+        ];
+        let mixed_json = literals_to_json(types(&mixed), &ranged);
+        assert_eq!(mixed_json["minimum"], json!(0.5));
+        assert_eq!(mixed_json["maximum"], json!(3));
+
+        let exclusive = CodegenOptions {
+            use_exclusive_minimum: true,
+            ..ranged
+        };
+        let exclusive_json = literals_to_json(types(&mixed), &exclusive);
+        assert_eq!(exclusive_json.get("minimum"), None);
+        assert_eq!(exclusive_json["exclusiveMinimum"], json!(0));
+        assert_eq!(exclusive_json["maximum"], json!(3));
+        assert_eq!(
+            literals_to_json(types(&integers), &exclusive)["minimum"],
+            json!(-7)
+        );
+
+        let strings = [
+            Literals::String(String::from("a")),
+            Literals::String(String::from("b")),
+        ];
+        assert_eq!(
+            literals_to_json(types(&strings), &exclusive),
+            json!({"type": "string"})
+        );
+    }
+
+    #[test]
+    fn lengths() {
+        let strings = || SubschemaTypes {
+            string_lengths: Some(LengthRange { min: 1, max: 8 }),
+            ..types(&[
+                Literals::String(String::from("a")),
+                Literals::String(String::from("abcdefgh")),
+            ])
+        };
+
+        assert_eq!(
+            literals_to_json(strings(), &CodegenOptions::new()),
+            json!({"type": "string"})
+        );
+
+        let lengths = CodegenOptions {
+            use_length: true,
+            ..CodegenOptions::new()
+        };
+
+        assert_eq!(
+            literals_to_json(strings(), &lengths),
+            json!({"type": "string", "minLength": 1, "maxLength": 8})
+        );
+    }
+
+    #[test]
+    fn items() {
+        let items = CodegenOptions {
+            use_items: true,
+            use_const: false,
+            use_enum: false,
+            ..CodegenOptions::new()
+        };
+        let data = |json| Subschema::from_data(DataValues::from(json));
+
+        assert_eq!(
+            subschema_to_json(data(json!([[1, 2], [3]])), &items)["items"],
+            json!({"type": "array", "items": {"type": "integer"}, "minItems": 1, "maxItems": 2, "uniqueItems": true})
+        );
+
+        assert_eq!(
+            subschema_to_json(data(json!([[1, 1], [3]])), &items)["items"],
+            json!({"type": "array", "items": {"type": "integer"}, "minItems": 1, "maxItems": 2})
+        );
+
+        assert_eq!(
+            subschema_to_json(data(json!([[1], []])), &items)["items"],
+            json!({"type": "array", "items": {"type": "integer"}, "minItems": 0, "maxItems": 1})
+        );
+    }
+
+    #[test]
+    fn tuples() {
+        let options = CodegenOptions {
+            use_const: false,
+            use_enum: false,
+            ..CodegenOptions::new()
+        };
+        let data = |json| Subschema::from_data(DataValues::from(json));
+
+        let tuple = subschema_to_json(data(json!([["a", 1, true], ["b", 2, null]])), &options);
+        assert_eq!(tuple["items"]["items"], json!(false));
+        assert_eq!(tuple["items"]["prefixItems"][0], json!({"type": "string"}));
+        assert_eq!(tuple["items"]["prefixItems"][1], json!({"type": "integer"}));
+        assert_eq!(
+            tuple["items"]["prefixItems"][2]["type"]
+                .as_array()
+                .map(|t| t.len()),
+            Some(2)
+        );
+
+        // Same kinds in every position, differing lengths, or a single sample are not tuples
+        for json in [
+            json!([[1.5, 2.5], [3.5, 4.5]]),
+            json!([["a", 1], ["b", 2, 3]]),
+            json!([["a", 1]]),
+            json!([["a", 1], [2, "b"]]),
+        ] {
+            assert!(
+                subschema_to_json(data(json.clone()), &options)["items"]
+                    .get("prefixItems")
+                    .is_none(),
+                "{} should not be a tuple",
+                json
+            );
+        }
+
+        let no_tuples = CodegenOptions {
+            use_tuples: false,
+            ..options
+        };
+        assert!(
+            subschema_to_json(data(json!([["a", 1], ["b", 2]])), &no_tuples)["items"]
+                .get("prefixItems")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn maps() {
+        let options = CodegenOptions {
+            map_threshold: 3,
+            use_const: false,
+            use_enum: false,
+            ..CodegenOptions::new()
+        };
+        let data = |json| Subschema::from_data(DataValues::from(json));
+
+        assert_eq!(
+            subschema_to_json(
+                data(json!({"101": {"n": 1}, "102": {"n": 2}, "205": {"n": 3, "m": true}})),
+                &options
+            ),
+            json!({
+                "type": "object",
+                "patternProperties": {"^[0-9]+$": subschema_to_json(
+                    data(json!([{"n": 1}, {"n": 3, "m": true}])),
+                    &options
+                )["items"]},
+                "additionalProperties": false
+            })
+        );
+
+        // Unpatterned keys need to mostly come and go between instances
+        let sparse = json!([{"alice": 1}, {"bob": 2}, {"carol": 3, "dave": 4}]);
+        assert_eq!(
+            subschema_to_json(data(sparse), &options)["items"],
+            json!({"type": "object", "additionalProperties": {"type": "integer"}})
+        );
+
+        // Rows of a table keep their columns, however the columns are named
+        let rows = json!([
+            {"col1": 1, "col2": 2, "col3": 3},
+            {"col1": 4, "col2": 5, "col3": 6}
+        ]);
+        assert!(
+            subschema_to_json(data(rows), &options)["items"]
+                .get("properties")
+                .is_some()
+        );
+
+        for json in [
+            // Record like, every key always present
+            json!({"alice": 1, "bob": 2, "carol": 3}),
+            // Below the threshold
+            json!({"1": 1, "2": 2}),
+            // Values of differing kinds
+            json!({"1": 1, "2": "two", "3": 3}),
+        ] {
+            assert!(
+                subschema_to_json(data(json.clone()), &options)
+                    .get("properties")
+                    .is_some(),
+                "{} should not be a map",
+                json
+            );
+        }
+
+        let no_map = CodegenOptions {
+            use_map: false,
+            ..options
+        };
+        assert!(
+            subschema_to_json(data(json!({"1": 1, "2": 2, "3": 3})), &no_map)
+                .get("properties")
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn variants() {
+        let options = CodegenOptions {
+            use_enum: false,
+            ..CodegenOptions::new()
+        };
+        // Discriminators are chosen once positions are folded, as when generating
+        let data = |json| {
+            let mut sb = Subschema::from_data(DataValues::from(json));
+            fold_positions(&mut sb, &CodegenOptions::new());
+            sb
+        };
+
+        let events = json!([
+            {"type": "click", "x": 1, "y": 2},
+            {"type": "key", "code": 65},
+            {"type": "click", "x": 3, "y": 4}
+        ]);
+
+        // Variants follow the order their tags were first seen
+        let variants = subschema_to_json(data(events), &options)["items"]["oneOf"]
+            .as_array()
+            .cloned()
+            .unwrap();
+
+        assert_eq!(variants.len(), 2);
+        assert_eq!(variants[0]["properties"]["type"], json!({"const": "click"}));
+        assert_eq!(variants[0]["properties"]["x"], json!({"type": "integer"}));
+        assert_eq!(variants[1]["properties"]["type"], json!({"const": "key"}));
+        assert_eq!(variants[1]["properties"]["code"], json!({"const": 65}));
+
+        // Variants stay records, even with keys that come and go
+        let bag = |start: usize| {
+            let mut bag = (start..start + 20)
+                .map(|i| (format!("k{}", i), json!("x")))
+                .collect::<serde_json::Map<_, _>>();
+            bag.insert(String::from("type"), json!("bag"));
+            Value::Object(bag)
+        };
+        let events = json!([bag(0), bag(20), {"type": "point", "x": 1}]);
+        let variants = subschema_to_json(data(events), &options)["items"]["oneOf"]
+            .as_array()
+            .cloned()
+            .unwrap();
+
+        assert_eq!(variants.len(), 2);
+        for variant in variants {
+            assert!(variant["properties"]["type"]["const"].is_string());
+        }
+
+        // Discriminators whose variants all share one shape stay merged
+        let uniform = json!([{"type": "a", "x": 1}, {"type": "b", "x": 2}, {"type": "a", "x": 3}]);
+        assert_eq!(
+            subschema_to_json(data(uniform), &options)["items"]["type"],
+            json!("object")
+        );
+
+        let no_variants = CodegenOptions {
+            use_variants: false,
+            ..options
+        };
+        let events = json!([
+            {"type": "click", "x": 1},
+            {"type": "key", "code": 65},
+            {"type": "click", "x": 2}
+        ]);
+        assert_eq!(
+            subschema_to_json(data(events), &no_variants)["items"]["type"],
+            json!("object")
+        );
+    }
+
+    #[test]
+    fn recursion() {
+        let tree = json!({"name": "a", "children": [{"name": "b", "children": [{"name": "c"}]}]});
+        let schema: Value = serde_json::from_str(&JsonSchema::generate(
+            Subschema::from_data(DataValues::from(tree)),
+            CodegenOptions::new(),
+        ))
+        .unwrap();
+
+        assert_eq!(schema["$ref"], json!("#/$defs/root"));
+        assert_eq!(
+            schema["$defs"]["root"]["properties"]["children"]["items"],
+            json!({"$ref": "#/$defs/root"})
+        );
+    }
+
+    #[test]
+    fn property_defs() {
+        let workbook = json!({"Sheet1": [{"a": 1}], "root": [{"b": "x"}]});
+        let mut options = CodegenOptions::new();
+        options.use_property_defs = true;
+        options.use_const = false;
+
+        let schema: Value = serde_json::from_str(&JsonSchema::generate(
+            Subschema::from_data(DataValues::from(workbook)),
+            options,
+        ))
+        .unwrap();
+
+        assert_eq!(
+            schema["properties"],
+            json!({
+                "Sheet1": {"$ref": "#/$defs/Sheet1"},
+                "root": {"$ref": "#/$defs/root"}
+            })
+        );
+        assert_eq!(schema["$defs"]["Sheet1"]["type"], json!("array"));
+        assert_eq!(
+            schema["$defs"]["root"]["items"]["properties"]["b"]["type"],
+            json!("string")
+        );
+
+        // Enough sheets for map detection still keep one definition each
+        let workbook = (1..=25)
+            .map(|i| (format!("Sheet{}", i), json!([{"n": i}])))
+            .collect::<serde_json::Map<_, _>>();
+        let mut options = CodegenOptions::new();
+        options.use_property_defs = true;
+
+        let schema: Value = serde_json::from_str(&JsonSchema::generate(
+            Subschema::from_data(DataValues::from(Value::Object(workbook))),
+            options,
+        ))
+        .unwrap();
+
+        assert!(schema.get("patternProperties").is_none());
+        assert_eq!(schema["properties"].as_object().unwrap().len(), 25);
+        assert_eq!(schema["$defs"].as_object().unwrap().len(), 25);
+        assert_eq!(
+            schema["properties"]["Sheet25"],
+            json!({"$ref": "#/$defs/Sheet25"})
+        );
+    }
+}
@@ -0,0 +1,460 @@
+use super::{
+    CodegenOptions, common_format,
+    definitions::{self, Path, References, Step},
+    detect_map, fold_positions, is_enum, is_tuple,
+    naming::{identifier, pascal_case, singular, unique},
+};
+use crate::state::{
+    LengthRange, Literals, StringFormat, Subschema, SubschemaArray, crunch_schemas,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Name given to the root type when no title is set
+const DEFAULT_ROOT_NAME: &str = "Root";
+
+/// Kind of value a field holds, as typed languages see it
+#[derive(Clone, PartialEq, Debug)]
+pub enum Shape {
+    /// Nothing known about the value, or only `null` was seen
+    Any,
+    Boolean,
+    Integer,
+    Number,
+    String(Option<StringFormat>),
+    /// Strings from a fixed set, named by an `Enumeration`
+    Enum(String),
+    Array(Box<Type>),
+    Tuple(Vec<Type>),
+    /// Object keyed by arbitrary strings
+    Map(Box<Type>),
+    /// Object with known keys, named by a `Record`
+    Record(String),
+    /// More than one kind of value
+    Union(Vec<Shape>),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Type {
+    pub shape: Shape,
+    /// `null` was seen alongside the shape
+    pub nullable: bool,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Field {
+    pub key: String,
+    pub value: Type,
+    pub required: bool,
+    /// Lengths of the strings seen, in unicode scalar values
+    pub lengths: Option<LengthRange>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Record {
+    pub name: String,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Enumeration {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+/// Named types a subschema breaks down into, shared by the typed language backends
+#[derive(Clone, PartialEq, Debug)]
+pub struct Model {
+    pub name: String,
+    pub root: Type,
+    /// Records in the order they are first reached from the root
+    pub records: Vec<Record>,
+    pub enums: Vec<Enumeration>,
+    /// Records which can contain themselves
+    pub recursive: HashSet<String>,
+}
+
+impl Model {
+    /// Breaks a subschema into named types, keeping clear of names the target language reserves
+    pub fn new(mut sb: Subschema, options: &CodegenOptions, reserved: &[&str]) -> Self {
+        let mut defs = definitions::Definitions::new();
+        let mut references = References::new();
+        let mut recursive_count = 0;
+
+        fold_positions(&mut sb, options);
+
+        if options.use_defs {
+            definitions::extract_recursion(&mut sb, &mut defs, &mut references);
+            recursive_count = defs.len();
+            definitions::extract_shared(&mut sb, &mut defs, &mut references);
+        }
+
+        let mut used = reserved
+            .iter()
+            .map(|r| r.to_string())
+            .collect::<HashSet<_>>();
+
+        let name = identifier(
+            options
+                .title
+                .as_deref()
+                .map(pascal_case)
+                .unwrap_or_default(),
+            DEFAULT_ROOT_NAME,
+        );
+        let name = unique(name, |n| used.contains(n));
+        used.insert(name.clone());
+
+        let mut builder = Builder {
+            options,
+            used,
+            definitions: HashMap::new(),
+            references,
+            records: Vec::new(),
+            enums: Vec::new(),
+        };
+
+        for (def, _) in &defs {
+            let record = if def == definitions::ROOT_NAME {
+                name.clone()
+            } else {
+                builder.name(def, "")
+            };
+
+            builder.definitions.insert(def.clone(), record);
+        }
+
+        let root = Path::root();
+        let root = if sb.object.is_some() && !builder.references.contains_key(&root) {
+            let nullable = has_null(&sb);
+            builder.record(sb, name.clone(), &root);
+
+            Type {
+                shape: Shape::Record(name.clone()),
+                nullable,
+            }
+        } else {
+            builder.value(sb, &name, "", &root)
+        };
+
+        let recursive = defs
+            .iter()
+            .take(recursive_count)
+            .map(|(def, _)| builder.definitions[def].clone())
+            .collect::<HashSet<_>>();
+
+        for (def, sb) in defs {
+            let record = builder.definitions[&def].clone();
+            builder.record(sb, record, &Path::definition(&def));
+        }
+
+        Self {
+            name,
+            root,
+            records: builder.records,
+            enums: builder.enums,
+            recursive,
+        }
+    }
+
+    /// The root is itself a record, needing no alias of its own
+    pub fn root_is_record(&self) -> bool {
+        !self.root.nullable && self.root.shape == Shape::Record(self.name.clone())
+    }
+
+    /// Whether any shape in the model, including those nested in collections, matches
+    pub fn any(&self, matches: impl Fn(&Shape) -> bool) -> bool {
+        let types = std::iter::once(&self.root).chain(
+            self.records
+                .iter()
+                .flat_map(|r| r.fields.iter().map(|f| &f.value)),
+        );
+
+        types.into_iter().any(|t| shape_any(&t.shape, &matches))
+    }
+}
+
+fn shape_any(shape: &Shape, matches: &impl Fn(&Shape) -> bool) -> bool {
+    matches(shape)
+        || match shape {
+            Shape::Array(t) | Shape::Map(t) => shape_any(&t.shape, matches),
+            Shape::Tuple(ts) => ts.iter().any(|t| shape_any(&t.shape, matches)),
+            Shape::Union(ss) => ss.iter().any(|s| shape_any(s, matches)),
+            _ => false,
+        }
+}
+
+fn has_null(sb: &Subschema) -> bool {
+    sb.types
+        .as_ref()
+        .is_some_and(|t| t.values.contains(&Literals::Null))
+}
+
+struct Builder<'a> {
+    options: &'a CodegenOptions,
+    used: HashSet<String>,
+    /// Definition name -> record name
+    definitions: HashMap<String, String>,
+    references: References,
+    records: Vec<Record>,
+    enums: Vec<Enumeration>,
+}
+
+impl Builder<'_> {
+    /// Claims a type name from a key, prefixing the parent's name and then numbering on collision
+    fn name(&mut self, key: &str, parent: &str) -> String {
+        let name = identifier(pascal_case(key), "Field");
+
+        let name = if self.used.contains(&name) && !parent.is_empty() {
+            format!("{}{}", parent, name)
+        } else {
+            name
+        };
+
+        let name = unique(name, |n| self.used.contains(n));
+        self.used.insert(name.clone());
+        name
+    }
+
+    fn record(&mut self, sb: Subschema, name: String, at: &Path) {
+        let index = self.records.len();
+        self.records.push(Record {
+            name: name.clone(),
+            fields: Vec::new(),
+        });
+
+        // Fields keep the order their keys were first seen in
+        let fields = sb
+            .object
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, property)| Field {
+                lengths: property.value.types.as_ref().and_then(|t| t.string_lengths),
+                value: self.value(
+                    property.value,
+                    &key,
+                    &name,
+                    &at.join(Step::Property(key.clone())),
+                ),
+                key,
+                required: property.required,
+            })
+            .collect();
+
+        self.records[index].fields = fields;
+    }
+
+    /// Type of a value found under `key`, naming any records and enums it needs
+    fn value(&mut self, sb: Subschema, key: &str, parent: &str, at: &Path) -> Type {
+        let nullable = has_null(&sb);
+        let mut shapes: Vec<Shape> = Vec::new();
+
+        if let Some(types) = &sb.types {
+            let values = types
+                .values
+                .iter()
+                .filter(|v| **v != Literals::Null)
+                .collect::<Vec<_>>();
+
+            let strings = values
+                .iter()
+                .filter_map(|v| match v {
+                    Literals::String(s) | Literals::Formatted(_, s) | Literals::Temporal(_, s) => {
+                        Some(s.clone())
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            if !strings.is_empty() && strings.len() == values.len() && is_enum(types, self.options)
+            {
+                let mut strings = strings;
+                strings.sort();
+
+                let name = self.name(key, parent);
+                self.enums.push(Enumeration {
+                    name: name.clone(),
+                    values: strings,
+                });
+                shapes.push(Shape::Enum(name));
+            } else {
+                let has = |f: fn(&Literals) -> bool| values.iter().any(|v| f(v));
+
+                if has(|v| matches!(v, Literals::Boolean(_))) {
+                    shapes.push(Shape::Boolean);
+                }
+
+                if has(|v| matches!(v, Literals::Float(_))) {
+                    shapes.push(Shape::Number);
+                } else if has(|v| matches!(v, Literals::Integer(_))) {
+                    shapes.push(Shape::Integer);
+                }
+
+                if !strings.is_empty() {
+                    let format = common_format(&types.values).filter(|_| self.options.use_format);
+                    shapes.push(Shape::String(format));
+                }
+            }
+        }
+
+        if let Some(array) = sb.array {
+            shapes.push(self.array(array, key, parent, at));
+        }
+
+        if let Some(reference) = self.references.get(at) {
+            shapes.push(Shape::Record(self.definitions[reference].clone()));
+        } else if let Some(object) = sb.object {
+            let shape = match detect_map(&object, sb.object_count, self.options) {
+                Some(_) => {
+                    let values = definitions::fold_values(&mut self.references, at, object.keys());
+
+                    let sb = object
+                        .into_values()
+                        .map(|p| p.value)
+                        .reduce(crunch_schemas)
+                        .unwrap_or(Subschema::new());
+
+                    let name = singular(key).unwrap_or(format!("{}Value", key));
+                    Shape::Map(Box::new(self.value(sb, &name, parent, &values)))
+                }
+                None => {
+                    let name = self.name(key, parent);
+                    let mut sb = Subschema::new();
+                    sb.object = Some(object);
+                    self.record(sb, name.clone(), at);
+                    Shape::Record(name)
+                }
+            };
+
+            shapes.push(shape);
+        }
+
+        let shape = match shapes.len() {
+            0 => Shape::Any,
+            1 => shapes.pop().unwrap(),
+            _ => Shape::Union(shapes),
+        };
+
+        Type { shape, nullable }
+    }
+
+    fn array(&mut self, array: SubschemaArray, key: &str, parent: &str, at: &Path) -> Shape {
+        let name = singular(key).unwrap_or(format!("{}Item", key));
+
+        match array.positions {
+            Some(positions)
+                if self.options.use_tuples && is_tuple(&positions, array.instance_count) =>
+            {
+                Shape::Tuple(
+                    positions
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, p)| self.value(p, &name, parent, &at.join(Step::Position(i))))
+                        .collect(),
+                )
+            }
+            positions => {
+                let items = SubschemaArray { positions, ..array }.into_items();
+
+                Shape::Array(Box::new(self.value(
+                    items,
+                    &name,
+                    parent,
+                    &at.join(Step::Items),
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Model, Shape, Type};
+    use crate::codegen::CodegenOptions;
+    use crate::state::{DataValues, StringFormat, Subschema};
+    use serde_json::json;
+
+    fn model(value: serde_json::Value, title: Option<&str>) -> Model {
+        let mut options = CodegenOptions::new();
+        options.title = title.map(String::from);
+        options.enum_threshold = 75;
+
+        Model::new(Subschema::from_data(DataValues::from(value)), &options, &[])
+    }
+
+    fn ty(shape: Shape) -> Type {
+        Type {
+            shape,
+            nullable: false,
+        }
+    }
+
+    #[test]
+    fn records() {
+        let users = model(
+            json!([
+                {"id": 1, "role": "admin", "address": {"city": "A", "zip": null}, "joined": "2024-01-01"},
+                {"id": 2, "role": "admin", "address": {"city": "B", "zip": "1"}},
+                {"id": 3, "role": "admin", "address": {"city": "C", "zip": "2"}, "joined": "2024-01-02"},
+                {"id": 4, "role": "user", "address": {"city": "D", "zip": "3"}, "score": 1.5}
+            ]),
+            Some("users"),
+        );
+
+        assert_eq!(users.name, "Users");
+        assert_eq!(
+            users.root,
+            ty(Shape::Array(Box::new(ty(Shape::Record(String::from(
+                "User"
+            ))))))
+        );
+        assert!(!users.root_is_record());
+
+        let names = users
+            .records
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["User", "Address"]);
+        assert_eq!(users.enums[0].name, "Role");
+        assert_eq!(users.enums[0].values, vec!["admin", "user"]);
+
+        let user = &users.records[0];
+        let keys = user
+            .fields
+            .iter()
+            .map(|f| f.key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["id", "role", "address", "joined", "score"]);
+        assert_eq!(user.fields[0].value, ty(Shape::Integer));
+        assert!(user.fields[0].required);
+        assert_eq!(user.fields[1].value, ty(Shape::Enum(String::from("Role"))));
+        assert_eq!(
+            user.fields[3].value,
+            ty(Shape::String(Some(StringFormat::Date)))
+        );
+        assert!(!user.fields[3].required);
+        assert_eq!(user.fields[4].value, ty(Shape::Number));
+
+        let zip = &users.records[1].fields[1];
+        assert_eq!(zip.value.shape, Shape::String(None));
+        assert!(zip.value.nullable);
+    }
+
+    #[test]
+    fn recursive() {
+        let tree = model(
+            json!({"name": "a", "children": [{"name": "b", "children": []}]}),
+            None,
+        );
+
+        assert!(tree.root_is_record());
+        assert!(tree.recursive.contains("Root"));
+        assert_eq!(tree.records.len(), 1);
+        assert_eq!(
+            tree.records[0].fields[1].value,
+            ty(Shape::Array(Box::new(ty(Shape::Record(String::from(
+                "Root"
+            ))))))
+        );
+    }
+}
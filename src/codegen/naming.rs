@@ -0,0 +1,130 @@
+/// Splits a key into words on punctuation, whitespace and case changes
+pub fn words(key: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let chars = key.chars().collect::<Vec<_>>();
+
+    for (i, c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        if let Some(prev) = current.chars().last() {
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+
+            // "userId" -> user Id, "HTTPServer" -> HTTP Server
+            let boundary = c.is_uppercase()
+                && (prev.is_lowercase()
+                    || prev.is_numeric()
+                    || (prev.is_uppercase() && next_lower));
+
+            if boundary {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+
+        current.push(*c);
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// `PascalCase`, for type names
+pub fn pascal_case(key: &str) -> String {
+    words(key).iter().map(|w| capitalize(w)).collect()
+}
+
+/// `snake_case`, for field names
+pub fn snake_case(key: &str) -> String {
+    words(key)
+        .iter()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Rough singular of an english plural, naming the elements of a collection
+pub fn singular(word: &str) -> Option<String> {
+    if !word.is_ascii() {
+        return None;
+    }
+
+    let lower = word.to_ascii_lowercase();
+
+    if lower.ends_with("ies") && word.len() > 4 {
+        Some(format!("{}y", &word[..word.len() - 3]))
+    } else if ["sses", "xes", "ches", "shes"]
+        .iter()
+        .any(|s| lower.ends_with(s))
+    {
+        Some(word[..word.len() - 2].to_owned())
+    } else if lower.ends_with('s') && !lower.ends_with("ss") && word.len() > 3 {
+        Some(word[..word.len() - 1].to_owned())
+    } else {
+        None
+    }
+}
+
+/// Makes a name usable as an identifier, which can't be empty or start with a digit
+pub fn identifier(name: String, fallback: &str) -> String {
+    match name.chars().next() {
+        None => fallback.to_owned(),
+        Some(c) if c.is_numeric() => format!("_{}", name),
+        Some(_) => name,
+    }
+}
+
+/// Appends the smallest number, from 2, that makes a name not yet used
+pub fn unique(name: String, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(&name) {
+        return name;
+    }
+
+    (2..)
+        .map(|n| format!("{}{}", name, n))
+        .find(|n| !taken(n))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pascal_case, singular, snake_case, words};
+
+    #[test]
+    fn cases() {
+        assert_eq!(words("userId"), vec!["user", "Id"]);
+        assert_eq!(words("HTTPServer_port"), vec!["HTTP", "Server", "port"]);
+        assert_eq!(words("@type"), vec!["type"]);
+        assert_eq!(words("--"), Vec::<String>::new());
+
+        assert_eq!(pascal_case("created-at"), "CreatedAt");
+        assert_eq!(pascal_case("Created at"), "CreatedAt");
+        assert_eq!(snake_case("createdAt"), "created_at");
+        assert_eq!(snake_case("address2Line"), "address2_line");
+
+        assert_eq!(singular("users").as_deref(), Some("user"));
+        assert_eq!(singular("Categories").as_deref(), Some("Category"));
+        assert_eq!(singular("boxes").as_deref(), Some("box"));
+        assert_eq!(singular("address"), None);
+        assert_eq!(singular("data"), None);
+    }
+}
@@ -0,0 +1,376 @@
+use super::{
+    CodegenOptions, Generation,
+    model::{Field, Model, Record, Shape, Type},
+    naming::{identifier, snake_case, unique},
+};
+use crate::state::Subschema;
+use std::collections::{HashMap, HashSet};
+
+/// Message wrapping the root when it isn't made of records, holding it in a single field
+const VALUE_FIELD: &str = "value";
+
+/// Field numbers kept back by protobuf for its own use
+const RESERVED_NUMBERS: std::ops::RangeInclusive<usize> = 19000..=19999;
+
+const WELL_KNOWN_VALUE: &str = "google.protobuf.Value";
+const WELL_KNOWN_LIST: &str = "google.protobuf.ListValue";
+const WELL_KNOWN_STRUCT: &str = "google.protobuf.Struct";
+
+/// Name protobuf's JSON mapping gives a field, in `lowerCamelCase`
+fn json_name(field: &str) -> String {
+    let mut name = String::new();
+    let mut upper = false;
+
+    for c in field.chars() {
+        match c {
+            '_' => upper = true,
+            c if upper => {
+                name.extend(c.to_uppercase());
+                upper = false;
+            }
+            c => name.push(c),
+        }
+    }
+
+    name
+}
+
+/// Names of the records and enums a shape refers to, through any collections
+fn named(shape: &Shape, names: &mut Vec<String>) {
+    match shape {
+        Shape::Enum(n) | Shape::Record(n) => names.push(n.clone()),
+        Shape::Array(t) | Shape::Map(t) => named(&t.shape, names),
+        Shape::Tuple(ts) => ts.iter().for_each(|t| named(&t.shape, names)),
+        Shape::Union(ss) => ss.iter().for_each(|s| named(s, names)),
+        _ => {}
+    }
+}
+
+struct File<'a> {
+    model: &'a Model,
+    /// Records and enums used by only one message, written inside it
+    nested: HashMap<String, Vec<String>>,
+    well_known: bool,
+}
+
+impl<'a> File<'a> {
+    fn new(model: &'a Model) -> Self {
+        let mut users: HashMap<String, Vec<String>> = HashMap::new();
+        let mut root = Vec::new();
+        named(&model.root.shape, &mut root);
+
+        for name in root {
+            users.entry(name).or_default().push(String::new());
+        }
+
+        for record in &model.records {
+            for field in &record.fields {
+                let mut names = Vec::new();
+                named(&field.value.shape, &mut names);
+
+                for name in names {
+                    users.entry(name).or_default().push(record.name.clone());
+                }
+            }
+        }
+
+        let mut nested: HashMap<String, Vec<String>> = HashMap::new();
+
+        let types = model
+            .enums
+            .iter()
+            .map(|e| &e.name)
+            .chain(model.records.iter().map(|r| &r.name));
+
+        for name in types {
+            match users.get(name).map(|u| u.as_slice()) {
+                Some([user])
+                    if !user.is_empty() && user != name && !model.recursive.contains(name) =>
+                {
+                    nested.entry(user.clone()).or_default().push(name.clone());
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            model,
+            nested,
+            well_known: false,
+        }
+    }
+
+    fn is_nested(&self, name: &str) -> bool {
+        self.nested.values().any(|n| n.iter().any(|n| n == name))
+    }
+
+    fn scalar(&mut self, shape: &Shape) -> String {
+        match shape {
+            Shape::Any | Shape::Union(_) => {
+                self.well_known = true;
+                String::from(WELL_KNOWN_VALUE)
+            }
+            Shape::Boolean => String::from("bool"),
+            Shape::Integer => String::from("int64"),
+            Shape::Number => String::from("double"),
+            Shape::String(_) => String::from("string"),
+            Shape::Enum(name) | Shape::Record(name) => name.clone(),
+            // Lists and maps can't be nested directly, so take the untyped well known types
+            Shape::Array(_) | Shape::Tuple(_) => {
+                self.well_known = true;
+                String::from(WELL_KNOWN_LIST)
+            }
+            Shape::Map(_) => {
+                self.well_known = true;
+                String::from(WELL_KNOWN_STRUCT)
+            }
+        }
+    }
+
+    /// Label and type of a field
+    fn field_type(&mut self, value: &Type, required: bool) -> String {
+        match &value.shape {
+            Shape::Array(t) => format!("repeated {}", self.scalar(&t.shape)),
+            Shape::Tuple(_) => {
+                self.well_known = true;
+                format!("repeated {}", WELL_KNOWN_VALUE)
+            }
+            Shape::Map(t) => format!("map<string, {}>", self.scalar(&t.shape)),
+            // Presence is how protobuf tells missing and null values apart from defaults
+            s if !required || value.nullable => format!("optional {}", self.scalar(s)),
+            s => self.scalar(s),
+        }
+    }
+
+    fn fields(&mut self, fields: &[Field], indent: &str) -> Vec<String> {
+        let mut names: HashSet<String> = HashSet::new();
+        let mut numbers = (1..).filter(|n| !RESERVED_NUMBERS.contains(n));
+
+        fields
+            .iter()
+            .map(|f| {
+                let name = identifier(snake_case(&f.key), "field");
+                let name = unique(name, |n| names.contains(n));
+                names.insert(name.clone());
+
+                let option = if json_name(&name) == f.key {
+                    String::new()
+                } else {
+                    format!(" [json_name = {}]", serde_json::to_string(&f.key).unwrap())
+                };
+
+                format!(
+                    "{}{} {} = {}{};",
+                    indent,
+                    self.field_type(&f.value, f.required),
+                    name,
+                    numbers.next().unwrap(),
+                    option
+                )
+            })
+            .collect()
+    }
+
+    fn enumeration(&self, name: &str, indent: &str) -> String {
+        let enumeration = self.model.enums.iter().find(|e| e.name == name).unwrap();
+
+        // Values share the scope of the enum, so they're prefixed with its name
+        let prefix = snake_case(name).to_uppercase();
+        let mut names = HashSet::from([format!("{}_UNSPECIFIED", prefix)]);
+
+        let mut values = vec![format!("{}  {}_UNSPECIFIED = 0;", indent, prefix)];
+
+        // The JSON mapping of proto3 uses value names, so the original value
+        // is kept alongside each one
+        for (i, original) in enumeration.values.iter().enumerate() {
+            let value = match snake_case(original).to_uppercase() {
+                v if v.is_empty() => format!("{}_EMPTY", prefix),
+                v => format!("{}_{}", prefix, v),
+            };
+            let value = unique(value, |n| names.contains(n));
+            names.insert(value.clone());
+
+            values.push(format!(
+                "{}  {} = {}; // {}",
+                indent,
+                value,
+                i + 1,
+                serde_json::to_string(original).unwrap()
+            ));
+        }
+
+        format!(
+            "{}enum {} {{\n{}\n{}}}",
+            indent,
+            name,
+            values.join("\n"),
+            indent
+        )
+    }
+
+    fn message(&mut self, record: &Record, indent: &str) -> String {
+        let inner = format!("{}  ", indent);
+        let mut body = self.fields(&record.fields, &inner);
+
+        for name in self.nested.get(&record.name).cloned().unwrap_or_default() {
+            let definition = match self.model.records.iter().find(|r| r.name == name) {
+                Some(r) => self.message(r, &inner),
+                None => self.enumeration(&name, &inner),
+            };
+
+            body.push(String::new());
+            body.push(definition);
+        }
+
+        if body.is_empty() {
+            return format!("{}message {} {{}}", indent, record.name);
+        }
+
+        format!(
+            "{}message {} {{\n{}\n{}}}",
+            indent,
+            record.name,
+            body.join("\n"),
+            indent
+        )
+    }
+}
+
+pub struct Proto {}
+
+impl Generation for Proto {
+    fn generate(sb: Subschema, options: CodegenOptions) -> String {
+        let model = Model::new(sb, &options, &[]);
+        let mut file = File::new(&model);
+
+        let mut items: Vec<String> = Vec::new();
+
+        // Protobuf messages are always records, other roots need one holding them
+        let rows = match &model.root.shape {
+            Shape::Record(_) => true,
+            Shape::Array(t) => matches!(t.shape, Shape::Record(_)),
+            _ => false,
+        };
+
+        if !rows {
+            let wrapper = Record {
+                name: model.name.clone(),
+                fields: vec![Field {
+                    key: VALUE_FIELD.to_owned(),
+                    value: model.root.clone(),
+                    required: true,
+                    lengths: None,
+                }],
+            };
+
+            items.push(file.message(&wrapper, ""));
+        }
+
+        for record in &model.records {
+            if !file.is_nested(&record.name) {
+                items.push(file.message(record, ""));
+            }
+        }
+
+        for enumeration in &model.enums {
+            if !file.is_nested(&enumeration.name) {
+                items.push(file.enumeration(&enumeration.name, ""));
+            }
+        }
+
+        let mut header = vec![String::from("syntax = \"proto3\";")];
+
+        if let Some(package) = options.title.as_deref().map(snake_case)
+            && package.starts_with(|c: char| c.is_ascii_alphabetic())
+        {
+            header.push(format!("package {};", package));
+        }
+
+        if file.well_known {
+            header.push(String::from("import \"google/protobuf/struct.proto\";"));
+        }
+
+        header
+            .into_iter()
+            .chain(items)
+            .collect::<Vec<_>>()
+            .join("\n\n")
+            + "\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Proto;
+    use crate::codegen::{CodegenOptions, Generation};
+    use crate::state::{DataValues, Subschema};
+    use serde_json::{Value, json};
+
+    fn generate(value: Value, title: Option<&str>) -> String {
+        let mut options = CodegenOptions::new();
+        options.title = title.map(String::from);
+        options.enum_threshold = 75;
+
+        Proto::generate(Subschema::from_data(DataValues::from(value)), options)
+    }
+
+    #[test]
+    fn messages() {
+        let code = generate(
+            json!([
+                {"orderId": 1, "status": "open", "lines": [{"sku": "a", "qty": 1}], "note": null},
+                {"orderId": 2, "status": "open", "lines": [], "note": "x", "meta": {"k": [1]}},
+                {"orderId": 3, "status": "shipped", "lines": [], "note": "y"},
+                {"orderId": 4, "status": "open", "lines": [], "note": "z", "shipping address": {"city": "c", "zip": "1"}}
+            ]),
+            Some("orders"),
+        );
+
+        assert_eq!(
+            code,
+            r#"syntax = "proto3";
+
+package orders;
+
+message Order {
+  int64 order_id = 1;
+  Status status = 2;
+  repeated Line lines = 3;
+  optional string note = 4;
+  optional Meta meta = 5;
+  optional ShippingAddress shipping_address = 6 [json_name = "shipping address"];
+
+  enum Status {
+    STATUS_UNSPECIFIED = 0;
+    STATUS_OPEN = 1; // "open"
+    STATUS_SHIPPED = 2; // "shipped"
+  }
+
+  message Line {
+    string sku = 1;
+    int64 qty = 2;
+  }
+
+  message Meta {
+    repeated int64 k = 1;
+  }
+
+  message ShippingAddress {
+    string city = 1;
+    string zip = 2;
+  }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn wrapped_root() {
+        let code = generate(json!([[1, 2], [3]]), None);
+
+        assert_eq!(
+            code,
+            "syntax = \"proto3\";\n\nimport \"google/protobuf/struct.proto\";\n\nmessage Root {\n  repeated google.protobuf.ListValue value = 1;\n}\n"
+        );
+    }
+}
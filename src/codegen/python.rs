@@ -0,0 +1,312 @@
+use super::{
+    CodegenOptions, Generation,
+    model::{Enumeration, Field, Model, Record, Shape, Type},
+    naming::{identifier, snake_case, unique},
+};
+use crate::state::Subschema;
+use std::collections::{BTreeSet, HashSet};
+
+/// Names the generated code refers to, which types can't take
+const RESERVED_TYPES: [&str; 6] = ["Any", "BaseModel", "Field", "Literal", "Optional", "Union"];
+
+const KEYWORDS: [&str; 35] = [
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+fn quote(s: &str) -> String {
+    serde_json::to_string(s).unwrap()
+}
+
+/// Python code being put together, noting which names need importing
+struct Module {
+    pydantic: bool,
+    typing: BTreeSet<&'static str>,
+    field: bool,
+}
+
+impl Module {
+    fn shape(&mut self, shape: &Shape) -> String {
+        match shape {
+            Shape::Any => {
+                self.typing.insert("Any");
+                String::from("Any")
+            }
+            Shape::Boolean => String::from("bool"),
+            Shape::Integer => String::from("int"),
+            Shape::Number => String::from("float"),
+            Shape::String(_) => String::from("str"),
+            Shape::Enum(name) | Shape::Record(name) => name.clone(),
+            Shape::Array(t) => format!("list[{}]", self.ty(t)),
+            Shape::Tuple(ts) => format!(
+                "tuple[{}]",
+                ts.iter().map(|t| self.ty(t)).collect::<Vec<_>>().join(", ")
+            ),
+            Shape::Map(t) => format!("dict[str, {}]", self.ty(t)),
+            Shape::Union(shapes) => {
+                self.typing.insert("Union");
+                format!(
+                    "Union[{}]",
+                    shapes
+                        .iter()
+                        .map(|s| self.shape(s))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }
+        }
+    }
+
+    fn ty(&mut self, ty: &Type) -> String {
+        match (&ty.shape, ty.nullable) {
+            // Nothing but null was seen
+            (Shape::Any, true) => String::from("None"),
+            (shape, true) => self.optional(shape),
+            (shape, false) => self.shape(shape),
+        }
+    }
+
+    fn optional(&mut self, shape: &Shape) -> String {
+        self.typing.insert("Optional");
+        format!("Optional[{}]", self.shape(shape))
+    }
+
+    fn field(&mut self, field: &Field, name: &str) -> String {
+        let annotation = match (&field.value.shape, field.required) {
+            (_, true) => self.ty(&field.value),
+            (Shape::Any, false) if field.value.nullable => String::from("None"),
+            (shape, false) => self.optional(shape),
+        };
+
+        let renamed = name != field.key;
+
+        let default = match (self.pydantic, renamed, field.required) {
+            (_, false, true) => None,
+            (_, false, false) => Some(String::from("None")),
+            (pydantic, true, required) => {
+                self.field = true;
+                let default = if required { "" } else { "default=None, " };
+
+                Some(if pydantic {
+                    format!("Field({}alias={})", default, quote(&field.key))
+                } else {
+                    format!(
+                        "field({}metadata={{\"alias\": {}}})",
+                        default,
+                        quote(&field.key)
+                    )
+                })
+            }
+        };
+
+        match default {
+            Some(d) => format!("    {}: {} = {}", name, annotation, d),
+            None => format!("    {}: {}", name, annotation),
+        }
+    }
+
+    fn record(&mut self, record: &Record) -> String {
+        let mut names: HashSet<String> = HashSet::new();
+
+        // Dataclass fields without a default can't follow those with one
+        let mut fields = record.fields.iter().collect::<Vec<_>>();
+        fields.sort_by_key(|f| !f.required);
+
+        let fields = fields
+            .into_iter()
+            .map(|f| {
+                let name = identifier(snake_case(&f.key), "field");
+                let name = if KEYWORDS.contains(&name.as_str()) {
+                    format!("{}_", name)
+                } else {
+                    name
+                };
+                let name = unique(name, |n| names.contains(n));
+                names.insert(name.clone());
+
+                self.field(f, &name)
+            })
+            .collect::<Vec<_>>();
+
+        let body = if fields.is_empty() {
+            String::from("    pass")
+        } else {
+            fields.join("\n")
+        };
+
+        if self.pydantic {
+            format!("class {}(BaseModel):\n{}", record.name, body)
+        } else {
+            format!("@dataclass\nclass {}:\n{}", record.name, body)
+        }
+    }
+
+    fn enumeration(&mut self, enumeration: &Enumeration) -> String {
+        self.typing.insert("Literal");
+
+        format!(
+            "{} = Literal[{}]",
+            enumeration.name,
+            enumeration
+                .values
+                .iter()
+                .map(|v| quote(v))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+
+    fn imports(&self) -> String {
+        let mut imports = vec![String::from("from __future__ import annotations")];
+        let mut libraries: Vec<String> = Vec::new();
+
+        if !self.pydantic {
+            let names = if self.field {
+                "dataclass, field"
+            } else {
+                "dataclass"
+            };
+            libraries.push(format!("from dataclasses import {}", names));
+        }
+
+        if !self.typing.is_empty() {
+            libraries.push(format!(
+                "from typing import {}",
+                self.typing.iter().cloned().collect::<Vec<_>>().join(", ")
+            ));
+        }
+
+        imports.push(libraries.join("\n"));
+
+        if self.pydantic {
+            let names = if self.field {
+                "BaseModel, Field"
+            } else {
+                "BaseModel"
+            };
+            imports.push(format!("from pydantic import {}", names));
+        }
+
+        imports.retain(|i| !i.is_empty());
+        imports.join("\n\n")
+    }
+}
+
+pub struct Python {}
+
+impl Generation for Python {
+    fn generate(sb: Subschema, options: CodegenOptions) -> String {
+        let model = Model::new(sb, &options, &RESERVED_TYPES);
+
+        let mut module = Module {
+            pydantic: options.use_pydantic,
+            typing: BTreeSet::new(),
+            field: false,
+        };
+
+        let enums = model
+            .enums
+            .iter()
+            .map(|e| module.enumeration(e))
+            .collect::<Vec<_>>();
+
+        let records = model
+            .records
+            .iter()
+            .map(|r| module.record(r))
+            .collect::<Vec<_>>();
+
+        // Evaluated when the module loads, so it has to come after the classes it names
+        let root = (!model.root_is_record())
+            .then(|| format!("{} = {}", model.name, module.ty(&model.root)));
+
+        let mut sections = vec![module.imports()];
+
+        if !enums.is_empty() {
+            sections.push(enums.join("\n"));
+        }
+
+        sections.extend(records);
+        sections.extend(root);
+
+        sections.join("\n\n\n") + "\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Python;
+    use crate::codegen::{CodegenOptions, Generation};
+    use crate::state::{DataValues, Subschema};
+    use serde_json::{Value, json};
+
+    fn generate(value: Value, pydantic: bool) -> String {
+        let mut options = CodegenOptions::new();
+        options.title = Some(String::from("rows"));
+        options.enum_threshold = 75;
+        options.use_pydantic = pydantic;
+
+        Python::generate(Subschema::from_data(DataValues::from(value)), options)
+    }
+
+    fn rows() -> Value {
+        json!([
+            {"id": 1, "kind": "a", "Full Name": "x", "score": 1.5, "pos": {"x": 1, "y": 2}},
+            {"id": 2, "kind": "a", "Full Name": null, "score": 2, "pos": {"x": 1, "y": 2}},
+            {"id": 3, "kind": "b", "Full Name": "y", "score": 3, "class": true},
+            {"id": 4, "kind": "a", "Full Name": "z", "score": 4}
+        ])
+    }
+
+    #[test]
+    fn dataclasses() {
+        assert_eq!(
+            generate(rows(), false),
+            r#"from __future__ import annotations
+
+from dataclasses import dataclass, field
+from typing import Literal, Optional
+
+
+Kind = Literal["a", "b"]
+
+
+@dataclass
+class Row:
+    id: int
+    kind: Kind
+    full_name: Optional[str] = field(metadata={"alias": "Full Name"})
+    score: float
+    pos: Optional[Pos] = None
+    class_: Optional[bool] = field(default=None, metadata={"alias": "class"})
+
+
+@dataclass
+class Pos:
+    x: int
+    y: int
+
+
+Rows = list[Row]
+"#
+        );
+    }
+
+    #[test]
+    fn pydantic() {
+        let code = generate(rows(), true);
+
+        assert!(code.starts_with(
+            "from __future__ import annotations\n\nfrom typing import Literal, Optional\n\nfrom pydantic import BaseModel, Field\n"
+        ));
+        assert!(code.contains(
+            "class Row(BaseModel):\n    id: int\n    kind: Kind\n    full_name: Optional[str] = Field(alias=\"Full Name\")\n"
+        ));
+        assert!(
+            code.contains("    class_: Optional[bool] = Field(default=None, alias=\"class\")\n")
+        );
+        assert!(code.contains("class Pos(BaseModel):\n    x: int\n    y: int\n"));
+    }
+}
@@ -0,0 +1,275 @@
+use super::{
+    CodegenOptions, Generation,
+    model::{Enumeration, Field, Model, Record, Shape, Type},
+    naming::{identifier, pascal_case, snake_case, unique},
+};
+use crate::state::Subschema;
+use std::collections::HashSet;
+
+/// Names the generated code refers to, which types can't take
+const RESERVED_TYPES: [&str; 8] = [
+    "Box",
+    "Deserialize",
+    "HashMap",
+    "Option",
+    "Self",
+    "Serialize",
+    "String",
+    "Vec",
+];
+
+const KEYWORDS: [&str; 51] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while",
+];
+
+/// Keywords which can't be used as raw identifiers either
+const UNRAWABLE: [&str; 4] = ["crate", "self", "Self", "super"];
+
+const DERIVE_RECORD: &str = "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]";
+const DERIVE_ENUM: &str =
+    "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]";
+
+fn escape_keyword(name: String) -> String {
+    if UNRAWABLE.contains(&name.as_str()) {
+        format!("{}_", name)
+    } else if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+fn escape_str(s: &str) -> String {
+    s.escape_default().to_string()
+}
+
+fn shape_to_rust(shape: &Shape) -> String {
+    match shape {
+        Shape::Any | Shape::Union(_) => String::from("serde_json::Value"),
+        Shape::Boolean => String::from("bool"),
+        Shape::Integer => String::from("i64"),
+        Shape::Number => String::from("f64"),
+        Shape::String(_) => String::from("String"),
+        Shape::Enum(name) | Shape::Record(name) => name.clone(),
+        Shape::Array(t) => format!("Vec<{}>", type_to_rust(t)),
+        Shape::Tuple(ts) => format!(
+            "({})",
+            ts.iter().map(type_to_rust).collect::<Vec<_>>().join(", ")
+        ),
+        Shape::Map(t) => format!("HashMap<String, {}>", type_to_rust(t)),
+    }
+}
+
+fn type_to_rust(ty: &Type) -> String {
+    // `serde_json::Value` already holds null
+    if ty.nullable && !matches!(ty.shape, Shape::Any | Shape::Union(_)) {
+        format!("Option<{}>", shape_to_rust(&ty.shape))
+    } else {
+        shape_to_rust(&ty.shape)
+    }
+}
+
+fn field_to_rust(field: &Field, name: &str, recursive: &HashSet<String>) -> String {
+    let mut serde: Vec<String> = Vec::new();
+
+    if name.trim_start_matches("r#") != field.key {
+        serde.push(format!("rename = \"{}\"", escape_str(&field.key)));
+    }
+
+    // Records directly holding themselves need indirection to have a size
+    let shape = match &field.value.shape {
+        Shape::Record(r) if recursive.contains(r) => format!("Box<{}>", r),
+        s => shape_to_rust(s),
+    };
+
+    let optional = !field.required || field.value.nullable;
+    let shape = match &field.value.shape {
+        Shape::Any | Shape::Union(_) if field.required => shape,
+        _ if optional => format!("Option<{}>", shape),
+        _ => shape,
+    };
+
+    if !field.required {
+        serde.push(String::from("skip_serializing_if = \"Option::is_none\""));
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+
+    if !serde.is_empty() {
+        lines.push(format!("    #[serde({})]", serde.join(", ")));
+    }
+
+    lines.push(format!("    pub {}: {},", name, shape));
+    lines.join("\n")
+}
+
+fn record_to_rust(record: &Record, recursive: &HashSet<String>) -> String {
+    let mut names: HashSet<String> = HashSet::new();
+
+    let fields = record
+        .fields
+        .iter()
+        .map(|f| {
+            let name = identifier(snake_case(&f.key), "field");
+            let name = unique(name, |n| names.contains(n));
+            names.insert(name.clone());
+
+            field_to_rust(f, &escape_keyword(name), recursive)
+        })
+        .collect::<Vec<_>>();
+
+    if fields.is_empty() {
+        return format!("{}\npub struct {} {{}}", DERIVE_RECORD, record.name);
+    }
+
+    format!(
+        "{}\npub struct {} {{\n{}\n}}",
+        DERIVE_RECORD,
+        record.name,
+        fields.join("\n")
+    )
+}
+
+fn enum_to_rust(enumeration: &Enumeration) -> String {
+    let mut names: HashSet<String> = HashSet::new();
+
+    let variants = enumeration
+        .values
+        .iter()
+        .map(|v| {
+            let name = identifier(pascal_case(v), "Empty");
+            let name = unique(name, |n| names.contains(n));
+            names.insert(name.clone());
+
+            let name = escape_keyword(name);
+
+            if name == *v {
+                format!("    {},", name)
+            } else {
+                format!(
+                    "    #[serde(rename = \"{}\")]\n    {},",
+                    escape_str(v),
+                    name
+                )
+            }
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        "{}\npub enum {} {{\n{}\n}}",
+        DERIVE_ENUM,
+        enumeration.name,
+        variants.join("\n")
+    )
+}
+
+pub struct Rust {}
+
+impl Generation for Rust {
+    fn generate(sb: Subschema, options: CodegenOptions) -> String {
+        let model = Model::new(sb, &options, &RESERVED_TYPES);
+
+        let mut imports = vec![String::from("use serde::{Deserialize, Serialize};")];
+
+        if model.any(|s| matches!(s, Shape::Map(_))) {
+            imports.push(String::from("use std::collections::HashMap;"));
+        }
+
+        let mut items = vec![imports.join("\n")];
+
+        if !model.root_is_record() {
+            items.push(format!(
+                "pub type {} = {};",
+                model.name,
+                type_to_rust(&model.root)
+            ));
+        }
+
+        items.extend(
+            model
+                .records
+                .iter()
+                .map(|r| record_to_rust(r, &model.recursive)),
+        );
+        items.extend(model.enums.iter().map(enum_to_rust));
+
+        items.join("\n\n") + "\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rust;
+    use crate::codegen::{CodegenOptions, Generation};
+    use crate::state::{DataValues, Subschema};
+    use serde_json::json;
+
+    fn generate(value: serde_json::Value, title: Option<&str>) -> String {
+        let mut options = CodegenOptions::new();
+        options.title = title.map(String::from);
+        options.enum_threshold = 75;
+
+        Rust::generate(Subschema::from_data(DataValues::from(value)), options)
+    }
+
+    #[test]
+    fn structs() {
+        let code = generate(
+            json!([
+                {"userId": 1, "type": "a", "tags": {}, "note": null, "extra": [1, "x"]},
+                {"userId": 2, "type": "a", "tags": {}, "note": "x", "extra": [2, "y"]},
+                {"userId": 3, "type": "b", "tags": {}, "note": null, "self": true},
+                {"userId": 4, "type": "a", "tags": {}, "note": "y"}
+            ]),
+            Some("events"),
+        );
+
+        assert_eq!(
+            code,
+            r#"use serde::{Deserialize, Serialize};
+
+pub type Events = Vec<Event>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    #[serde(rename = "userId")]
+    pub user_id: i64,
+    pub r#type: Type,
+    pub tags: Tags,
+    pub note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra: Option<(i64, String)>,
+    #[serde(rename = "self", skip_serializing_if = "Option::is_none")]
+    pub self_: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tags {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Type {
+    #[serde(rename = "a")]
+    A,
+    #[serde(rename = "b")]
+    B,
+}
+"#
+        );
+    }
+
+    #[test]
+    fn recursion() {
+        let code = generate(
+            json!({"value": 1, "next": {"value": 2, "next": {"value": 3, "next": null}}}),
+            Some("linked list"),
+        );
+
+        assert!(code.contains("pub struct LinkedList {"));
+        assert!(code.contains("pub next: Option<Box<LinkedList>>,"));
+        assert!(!code.contains("pub type"));
+    }
+}
@@ -0,0 +1,199 @@
+use super::{
+    CodegenOptions, Generation,
+    model::{Field, Model, Shape, Type},
+    naming::snake_case,
+};
+use crate::state::{LengthRange, StringFormat, Subschema};
+use clap::ValueEnum;
+
+/// Table name used when no title is set
+const DEFAULT_TABLE: &str = "root";
+
+/// Column holding the whole value when the input isn't made of records
+const VALUE_COLUMN: &str = "value";
+
+// Longest string MySQL keeps in a `VARCHAR` under its row size limit, at 4 bytes a character
+const MYSQL_VARCHAR_MAX: usize = 16383;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum SqlDialect {
+    #[value(alias = "postgresql")]
+    Postgres,
+    Sqlite,
+    Mysql,
+}
+
+impl SqlDialect {
+    fn quote(self, name: &str) -> String {
+        match self {
+            Self::Mysql => format!("`{}`", name.replace('`', "``")),
+            Self::Postgres | Self::Sqlite => format!("\"{}\"", name.replace('"', "\"\"")),
+        }
+    }
+
+    fn column_type(self, shape: &Shape, lengths: Option<LengthRange>) -> String {
+        let kind = match (self, shape) {
+            (Self::Postgres | Self::Mysql, Shape::Boolean) => "BOOLEAN",
+            (Self::Postgres | Self::Mysql, Shape::Integer) => "BIGINT",
+            (Self::Postgres, Shape::Number) => "DOUBLE PRECISION",
+            (Self::Mysql, Shape::Number) => "DOUBLE",
+            (Self::Postgres, Shape::String(Some(StringFormat::DateTime))) => "TIMESTAMPTZ",
+            (Self::Mysql, Shape::String(Some(StringFormat::DateTime))) => "DATETIME",
+            (Self::Postgres | Self::Mysql, Shape::String(Some(StringFormat::Date))) => "DATE",
+            (Self::Postgres | Self::Mysql, Shape::String(Some(StringFormat::Time))) => "TIME",
+            (Self::Postgres, Shape::String(Some(StringFormat::Uuid))) => "UUID",
+            (Self::Postgres | Self::Mysql, Shape::String(_) | Shape::Enum(_)) => {
+                return self.varchar(lengths);
+            }
+            (
+                Self::Postgres,
+                Shape::Array(_) | Shape::Tuple(_) | Shape::Map(_) | Shape::Record(_),
+            ) => "JSONB",
+            (Self::Mysql, Shape::Array(_) | Shape::Tuple(_) | Shape::Map(_) | Shape::Record(_)) => {
+                "JSON"
+            }
+            // SQLite keeps booleans as integers, and has no date or json types
+            (Self::Sqlite, Shape::Boolean | Shape::Integer) => "INTEGER",
+            (Self::Sqlite, Shape::Number) => "REAL",
+            _ => "TEXT",
+        };
+
+        kind.to_owned()
+    }
+
+    fn varchar(self, lengths: Option<LengthRange>) -> String {
+        match lengths {
+            Some(l) if l.max > 0 && (self != Self::Mysql || l.max <= MYSQL_VARCHAR_MAX) => {
+                format!("VARCHAR({})", l.max)
+            }
+            _ => String::from("TEXT"),
+        }
+    }
+}
+
+fn column(field: &Field, dialect: SqlDialect) -> String {
+    let Type { shape, nullable } = &field.value;
+
+    // Columns never missing nor null
+    let not_null = if field.required && !nullable && *shape != Shape::Any {
+        " NOT NULL"
+    } else {
+        ""
+    };
+
+    format!(
+        "    {} {}{}",
+        dialect.quote(&field.key),
+        dialect.column_type(shape, field.lengths),
+        not_null
+    )
+}
+
+pub struct Sql {}
+
+impl Generation for Sql {
+    fn generate(sb: Subschema, options: CodegenOptions) -> String {
+        let lengths = sb.types.as_ref().and_then(|t| t.string_lengths);
+        let model = Model::new(sb, &options, &[]);
+        let dialect = options.sql_dialect;
+
+        // Rows are the records of the root, or its array items
+        let row = match &model.root.shape {
+            Shape::Record(r) => Some(r),
+            Shape::Array(t) => match &t.shape {
+                Shape::Record(r) => Some(r),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let columns = match row.and_then(|r| model.records.iter().find(|rc| rc.name == *r)) {
+            Some(record) => record.fields.clone(),
+            None => vec![Field {
+                key: VALUE_COLUMN.to_owned(),
+                value: model.root.clone(),
+                required: true,
+                lengths,
+            }],
+        };
+
+        let table = match options.title.as_deref().map(snake_case) {
+            Some(t) if !t.is_empty() => t,
+            _ => DEFAULT_TABLE.to_owned(),
+        };
+
+        let columns = columns
+            .iter()
+            .map(|c| column(c, dialect))
+            .collect::<Vec<_>>();
+
+        if columns.is_empty() {
+            return format!("CREATE TABLE {} ();\n", dialect.quote(&table));
+        }
+
+        format!(
+            "CREATE TABLE {} (\n{}\n);\n",
+            dialect.quote(&table),
+            columns.join(",\n")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sql, SqlDialect};
+    use crate::codegen::{CodegenOptions, Generation};
+    use crate::filetype::{CsvFileType, CsvOptions, Filetype};
+    use crate::state::Subschema;
+
+    const CSV: &str = "id,name,price,active,joined,updated,nick
+1,Ann,1.5,true,2024-01-01,2024-01-01T10:00:00Z,
+2,Bo,2,false,2024-02-01,2024-02-01T10:00:00Z,b
+3,Cyrus,,true,2024-03-01,2024-03-01T10:00:00Z,cy";
+
+    fn generate(dialect: SqlDialect) -> String {
+        let data = CsvFileType::new(CSV, CsvOptions::new())
+            .unwrap()
+            .to_object();
+
+        let mut options = CodegenOptions::new();
+        options.title = Some(String::from("Customer Accounts"));
+        options.use_enum = false;
+        options.sql_dialect = dialect;
+
+        Sql::generate(Subschema::from_data(data), options)
+    }
+
+    #[test]
+    fn dialects() {
+        assert_eq!(
+            generate(SqlDialect::Postgres),
+            r#"CREATE TABLE "customer_accounts" (
+    "id" BIGINT NOT NULL,
+    "name" VARCHAR(5) NOT NULL,
+    "price" DOUBLE PRECISION,
+    "active" BOOLEAN NOT NULL,
+    "joined" DATE NOT NULL,
+    "updated" TIMESTAMPTZ NOT NULL,
+    "nick" VARCHAR(2)
+);
+"#
+        );
+
+        assert_eq!(
+            generate(SqlDialect::Sqlite),
+            r#"CREATE TABLE "customer_accounts" (
+    "id" INTEGER NOT NULL,
+    "name" TEXT NOT NULL,
+    "price" REAL,
+    "active" INTEGER NOT NULL,
+    "joined" TEXT NOT NULL,
+    "updated" TEXT NOT NULL,
+    "nick" TEXT
+);
+"#
+        );
+
+        assert!(generate(SqlDialect::Mysql).contains("    `updated` DATETIME NOT NULL,\n"));
+    }
+}
@@ -0,0 +1,181 @@
+use super::{
+    CodegenOptions, Generation,
+    model::{Enumeration, Field, Model, Record, Shape, Type},
+};
+use crate::state::Subschema;
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Names the generated code refers to, which types can't take
+const RESERVED_TYPES: [&str; 2] = ["Array", "Record"];
+
+static IDENTIFIER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z_$][A-Za-z0-9_$]*$").unwrap());
+
+fn quote(s: &str) -> String {
+    serde_json::to_string(s).unwrap()
+}
+
+fn shape_to_ts(shape: &Shape) -> String {
+    match shape {
+        Shape::Any => String::from("unknown"),
+        Shape::Boolean => String::from("boolean"),
+        Shape::Integer | Shape::Number => String::from("number"),
+        Shape::String(_) => String::from("string"),
+        Shape::Enum(name) | Shape::Record(name) => name.clone(),
+        Shape::Array(t) => {
+            let items = type_to_ts(t);
+
+            if items.contains(' ') {
+                format!("({})[]", items)
+            } else {
+                format!("{}[]", items)
+            }
+        }
+        Shape::Tuple(ts) => format!(
+            "[{}]",
+            ts.iter().map(type_to_ts).collect::<Vec<_>>().join(", ")
+        ),
+        Shape::Map(t) => format!("Record<string, {}>", type_to_ts(t)),
+        Shape::Union(shapes) => shapes
+            .iter()
+            .map(shape_to_ts)
+            .collect::<Vec<_>>()
+            .join(" | "),
+    }
+}
+
+fn type_to_ts(ty: &Type) -> String {
+    match (&ty.shape, ty.nullable) {
+        // Nothing but null was seen
+        (Shape::Any, true) => String::from("null"),
+        (shape, true) => format!("{} | null", shape_to_ts(shape)),
+        (shape, false) => shape_to_ts(shape),
+    }
+}
+
+fn field_to_ts(field: &Field) -> String {
+    let key = if IDENTIFIER.is_match(&field.key) {
+        field.key.clone()
+    } else {
+        quote(&field.key)
+    };
+
+    let optional = if field.required { "" } else { "?" };
+
+    format!("  {}{}: {};", key, optional, type_to_ts(&field.value))
+}
+
+fn record_to_ts(record: &Record) -> String {
+    if record.fields.is_empty() {
+        return format!("export interface {} {{}}", record.name);
+    }
+
+    format!(
+        "export interface {} {{\n{}\n}}",
+        record.name,
+        record
+            .fields
+            .iter()
+            .map(field_to_ts)
+            .collect::<Vec<_>>()
+            .join("\n")
+    )
+}
+
+fn enum_to_ts(enumeration: &Enumeration) -> String {
+    format!(
+        "export type {} = {};",
+        enumeration.name,
+        enumeration
+            .values
+            .iter()
+            .map(|v| quote(v))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    )
+}
+
+pub struct TypeScript {}
+
+impl Generation for TypeScript {
+    fn generate(sb: Subschema, options: CodegenOptions) -> String {
+        let model = Model::new(sb, &options, &RESERVED_TYPES);
+
+        let mut items: Vec<String> = Vec::new();
+
+        if !model.root_is_record() {
+            items.push(format!(
+                "export type {} = {};",
+                model.name,
+                type_to_ts(&model.root)
+            ));
+        }
+
+        items.extend(model.records.iter().map(record_to_ts));
+        items.extend(model.enums.iter().map(enum_to_ts));
+
+        items.join("\n\n") + "\n"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TypeScript;
+    use crate::codegen::{CodegenOptions, Generation};
+    use crate::state::{DataValues, Subschema};
+    use serde_json::json;
+
+    fn generate(value: serde_json::Value, title: Option<&str>) -> String {
+        let mut options = CodegenOptions::new();
+        options.title = title.map(String::from);
+        options.enum_threshold = 75;
+
+        TypeScript::generate(Subschema::from_data(DataValues::from(value)), options)
+    }
+
+    #[test]
+    fn interfaces() {
+        let code = generate(
+            json!([
+                {"id": 1, "status": "open", "user name": "a", "labels": ["x", null], "pos": [1, "a"]},
+                {"id": 2, "status": "open", "user name": null, "labels": [], "pos": [2, "b"]},
+                {"id": 3, "status": "closed", "user name": "b", "labels": [], "owner": {"id": 1}},
+                {"id": 4.5, "status": "open", "user name": "c", "labels": [], "extra": null}
+            ]),
+            Some("issues"),
+        );
+
+        assert_eq!(
+            code,
+            r#"export type Issues = Issue[];
+
+export interface Issue {
+  id: number;
+  status: Status;
+  "user name": string | null;
+  labels: (string | null)[];
+  pos?: [number, string];
+  owner?: Owner;
+  extra?: null;
+}
+
+export interface Owner {
+  id: number;
+}
+
+export type Status = "closed" | "open";
+"#
+        );
+    }
+
+    #[test]
+    fn root_record() {
+        let code = generate(json!({"a": 1, "b": [true, "x"]}), None);
+
+        assert_eq!(
+            code,
+            "export interface Root {\n  a: number;\n  b: (boolean | string)[];\n}\n"
+        );
+    }
+}
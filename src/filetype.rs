@@ -2,9 +2,21 @@ use crate::state::DataValues;
 
 mod csvft;
 mod jsonft;
+mod jsonlft;
+mod spreadsheetft;
+mod sqliteft;
+mod tomlft;
+mod xmlft;
+mod yamlft;
 
-pub use csvft::{CsvFileType, CsvOptions};
-pub use jsonft::JsonFileType;
+pub use csvft::{CsvFileType, CsvOptions, CsvRecords};
+pub use jsonft::{JsonFileType, JsonRecords};
+pub use jsonlft::{JsonlFileType, JsonlRecords};
+pub use spreadsheetft::{SpreadsheetFileType, SpreadsheetOptions};
+pub use sqliteft::{SqliteFileType, SqliteOptions};
+pub use tomlft::TomlFileType;
+pub use xmlft::{XmlFileType, XmlOptions};
+pub use yamlft::{YamlFileType, YamlOptions};
 
 pub trait Filetype {
     fn to_object(self) -> DataValues;
@@ -1,4 +1,5 @@
-use std::{collections::HashMap, error::Error};
+use indexmap::IndexMap;
+use std::{error::Error, io::Read};
 
 use super::Filetype;
 use crate::state::{DataValues, Literals};
@@ -14,23 +15,32 @@ impl CsvOptions {
 }
 
 pub struct CsvFileType {
-    objects: Vec<HashMap<String, Literals>>,
+    objects: Vec<IndexMap<String, Literals>>,
+}
+
+fn open_reader<R: Read>(
+    input: R,
+    options: &CsvOptions,
+) -> Result<(csv::Reader<R>, Vec<String>), Box<dyn Error>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .delimiter(options.delimiter.try_into()?)
+        .from_reader(input);
+
+    let fields = reader
+        .headers()?
+        .iter()
+        .map(|s| s.to_owned())
+        .collect::<Vec<_>>();
+
+    Ok((reader, fields))
 }
 
 impl CsvFileType {
     pub fn new(file: &str, options: CsvOptions) -> Result<Self, Box<dyn Error>> {
-        let mut reader = csv::ReaderBuilder::new()
-            .has_headers(true)
-            .delimiter(options.delimiter.try_into()?)
-            .from_reader(file.as_bytes());
-
-        let fields = reader
-            .headers()?
-            .iter()
-            .map(|s| s.to_owned())
-            .collect::<Vec<_>>();
+        let (mut reader, fields) = open_reader(file.as_bytes(), &options)?;
 
-        let mut objs: Vec<HashMap<String, Literals>> = Vec::new();
+        let mut objs: Vec<IndexMap<String, Literals>> = Vec::new();
 
         for result in reader.records() {
             objs.push(
@@ -63,6 +73,42 @@ impl Filetype for CsvFileType {
     }
 }
 
+/// Reads csv rows one at a time, yielding each as an object record
+pub struct CsvRecords<R: Read> {
+    fields: Vec<String>,
+    records: csv::StringRecordsIntoIter<R>,
+}
+
+impl<R: Read> CsvRecords<R> {
+    pub fn new(input: R, options: CsvOptions) -> Result<Self, Box<dyn Error>> {
+        let (reader, fields) = open_reader(input, &options)?;
+
+        Ok(Self {
+            fields,
+            records: reader.into_records(),
+        })
+    }
+}
+
+impl<R: Read> Iterator for CsvRecords<R> {
+    type Item = Result<DataValues, Box<dyn Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.records.next()? {
+            Ok(r) => r,
+            Err(e) => return Some(Err(e.into())),
+        };
+
+        Some(Ok(DataValues::Object(
+            self.fields
+                .iter()
+                .zip(record.iter())
+                .map(|(k, v)| (k.to_owned(), DataValues::Literal(Literals::from(v))))
+                .collect(),
+        )))
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use super::*;
@@ -1,6 +1,7 @@
 use super::Filetype;
 use crate::state::DataValues;
 use serde_json::Value;
+use std::{error::Error, io::BufRead};
 
 pub struct JsonFileType {
     json: Value,
@@ -20,12 +21,136 @@ impl Filetype for JsonFileType {
     }
 }
 
+/// Reads the elements of a top-level json array one at a time, without parsing the array as a whole
+pub struct JsonRecords<R: BufRead> {
+    reader: R,
+    started: bool,
+    finished: bool,
+}
+
+impl<R: BufRead> JsonRecords<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            started: false,
+            finished: false,
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Box<dyn Error>> {
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+
+    fn skip_whitespace(&mut self) -> Result<Option<u8>, Box<dyn Error>> {
+        while let Some(b) = self.peek()? {
+            if !b.is_ascii_whitespace() {
+                return Ok(Some(b));
+            }
+            self.reader.consume(1);
+        }
+
+        Ok(None)
+    }
+
+    /// Consumes the separator before the next element, returning false once the array is closed
+    fn next_separator(&mut self) -> Result<bool, Box<dyn Error>> {
+        let expected = if self.started { b',' } else { b'[' };
+
+        match self.skip_whitespace()? {
+            Some(b) if b == expected => self.reader.consume(1),
+            Some(b']') if self.started => {
+                self.reader.consume(1);
+
+                return match self.skip_whitespace()? {
+                    None => Ok(false),
+                    Some(_) => Err("Unexpected data after closing top-level array".into()),
+                };
+            }
+            _ if self.started => return Err("Expected `,` or `]` between array elements".into()),
+            _ => return Err("Streamed json input must be a top-level array".into()),
+        };
+
+        if !self.started {
+            self.started = true;
+
+            if self.skip_whitespace()? == Some(b']') {
+                return self.next_separator();
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Collects the raw bytes of one element, stopping before the `,` or `]` that ends it
+    fn next_element(&mut self) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut element: Vec<u8> = Vec::new();
+        let mut depth: usize = 0;
+        let mut in_string = false;
+        let mut escaped = false;
+
+        loop {
+            let Some(b) = self.peek()? else {
+                return Err("Unexpected end of input inside top-level array".into());
+            };
+
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if b == b'\\' {
+                    escaped = true;
+                } else if b == b'"' {
+                    in_string = false;
+                }
+            } else {
+                match b {
+                    b'"' => in_string = true,
+                    b'[' | b'{' => depth += 1,
+                    b']' | b'}' if depth == 0 => return Ok(element),
+                    b']' | b'}' => depth -= 1,
+                    b',' if depth == 0 => return Ok(element),
+                    _ => {}
+                }
+            }
+
+            element.push(b);
+            self.reader.consume(1);
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for JsonRecords<R> {
+    type Item = Result<DataValues, Box<dyn Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let record = match self.next_separator() {
+            Ok(true) => self.next_element().and_then(|element| {
+                Ok(DataValues::from(serde_json::from_slice::<Value>(&element)?))
+            }),
+            Ok(false) => {
+                self.finished = true;
+                return None;
+            }
+            Err(e) => Err(e),
+        };
+
+        if record.is_err() {
+            self.finished = true;
+        }
+
+        Some(record)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::Filetype;
-    use super::JsonFileType as jft;
+    use super::{JsonFileType as jft, JsonRecords};
     use crate::state::{DataValues as DV, Literals as LT};
-    use std::collections::HashMap;
+    use indexmap::IndexMap;
 
     #[test]
     fn json() {
@@ -45,7 +170,7 @@ mod tests {
         );
 
         let obj_empty = jft::new("{}").unwrap();
-        assert_eq!(obj_empty.to_object(), DV::Object(HashMap::new()));
+        assert_eq!(obj_empty.to_object(), DV::Object(IndexMap::new()));
 
         let obj_basic = jft::new("{\"a\": 1, \"b\": \"abc\"}").unwrap();
         assert_eq!(
@@ -73,7 +198,7 @@ mod tests {
                 DV::Literal(LT::Integer(1)),
                 DV::Literal(LT::Float((3.2_f64).to_bits())),
                 DV::Literal(LT::Boolean(true)),
-                DV::Object(HashMap::new()),
+                DV::Object(IndexMap::new()),
                 DV::Array(Vec::new())
             ])
         );
@@ -120,4 +245,28 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn json_records() {
+        fn stream(input: &str) -> Result<Vec<DV>, Box<dyn std::error::Error>> {
+            JsonRecords::new(input.as_bytes()).collect()
+        }
+
+        for input in [
+            "[]",
+            " [ ] ",
+            "[1]",
+            "[1, -2.5e3, \"a,]\\\"}\", null, true]",
+            "[{\"a\": [1, {\"b\": \"]\"}]}, [[], {}], {}]\n",
+        ] {
+            assert_eq!(
+                DV::Array(stream(input).unwrap()),
+                jft::new(input).unwrap().to_object()
+            );
+        }
+
+        for input in ["", "{}", "1", "[1", "[1,", "[1 2]", "[1] 2", "[1,]"] {
+            assert!(stream(input).is_err(), "{:?} should not stream", input);
+        }
+    }
 }
@@ -0,0 +1,145 @@
+use super::Filetype;
+use crate::state::DataValues;
+use serde_json::Value;
+use std::{
+    error::Error,
+    io::{BufRead, Lines},
+};
+
+pub struct JsonlFileType {
+    records: Vec<Value>,
+}
+
+impl JsonlFileType {
+    pub fn new(file: &str) -> Result<Self, Box<dyn Error>> {
+        let mut records: Vec<Value> = Vec::new();
+
+        for (number, line) in file.lines().enumerate() {
+            // Blank lines (including a trailing newline) carry no record
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let record =
+                serde_json::from_str(line).map_err(|e| format!("Line {}: {}", number + 1, e))?;
+
+            records.push(record);
+        }
+
+        Ok(Self { records })
+    }
+}
+
+impl Filetype for JsonlFileType {
+    fn to_object(self) -> DataValues {
+        DataValues::Array(self.records.into_iter().map(DataValues::from).collect())
+    }
+}
+
+/// Reads json lines one at a time, yielding each line as a record
+pub struct JsonlRecords<R: BufRead> {
+    lines: Lines<R>,
+    number: usize,
+}
+
+impl<R: BufRead> JsonlRecords<R> {
+    pub fn new(input: R) -> Self {
+        Self {
+            lines: input.lines(),
+            number: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for JsonlRecords<R> {
+    type Item = Result<DataValues, Box<dyn Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.number += 1;
+
+            let line = match self.lines.next()? {
+                Ok(l) => l,
+                Err(e) => return Some(Err(e.into())),
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            return Some(
+                serde_json::from_str::<Value>(&line)
+                    .map(DataValues::from)
+                    .map_err(|e| format!("Line {}: {}", self.number, e).into()),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Filetype;
+    use super::{JsonlFileType as jlft, JsonlRecords};
+    use crate::state::{DataValues as DV, Literals as LT};
+    use indexmap::IndexMap;
+
+    #[test]
+    fn jsonl() {
+        let empty = jlft::new("").unwrap();
+        assert_eq!(empty.to_object(), DV::Array(Vec::new()));
+
+        let basic = jlft::new("{\"a\": 1}\n\n{\"a\": 2, \"b\": null}\n").unwrap();
+        assert_eq!(
+            basic.to_object(),
+            DV::Array(vec![
+                DV::Object(
+                    vec![(String::from("a"), DV::Literal(LT::Integer(1)))]
+                        .into_iter()
+                        .collect()
+                ),
+                DV::Object(
+                    vec![
+                        (String::from("a"), DV::Literal(LT::Integer(2))),
+                        (String::from("b"), DV::Literal(LT::Null))
+                    ]
+                    .into_iter()
+                    .collect()
+                )
+            ])
+        );
+
+        let mixed = jlft::new("1\r\n\"x\"\r\n[]\r\n{}").unwrap();
+        assert_eq!(
+            mixed.to_object(),
+            DV::Array(vec![
+                DV::Literal(LT::Integer(1)),
+                DV::Literal(LT::String(String::from("x"))),
+                DV::Array(Vec::new()),
+                DV::Object(IndexMap::new())
+            ])
+        );
+
+        assert!(jlft::new("{\"a\": 1}\n{\"a\":").is_err());
+    }
+
+    #[test]
+    fn jsonl_records() {
+        const INPUT: &str = "{\"a\": 1}\n\n[true]\n\"x\"\n";
+
+        let streamed = JsonlRecords::new(INPUT.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(DV::Array(streamed), jlft::new(INPUT).unwrap().to_object());
+
+        let mut broken = JsonlRecords::new("1\n\n{\n".as_bytes());
+        assert_eq!(broken.next().unwrap().unwrap(), DV::Literal(LT::Integer(1)));
+        assert!(
+            broken
+                .next()
+                .unwrap()
+                .unwrap_err()
+                .to_string()
+                .starts_with("Line 3")
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use super::Filetype;
+use crate::state::{DataValues, Literals, StringFormat};
+use calamine::{Data, DataType, Range, Reader, open_workbook_auto_from_rs};
+use indexmap::IndexMap;
+use std::{error::Error, io::Cursor};
+
+// Largest float every integer up to is exactly representable
+const FLOAT_INTEGER_MAX: f64 = 9007199254740992.0;
+
+pub struct SpreadsheetOptions {
+    pub sheet: Option<String>,
+}
+
+impl SpreadsheetOptions {
+    pub fn new() -> Self {
+        Self { sheet: None }
+    }
+}
+
+pub struct SpreadsheetFileType {
+    sheets: Vec<(String, Vec<IndexMap<String, Literals>>)>,
+    combined: bool,
+}
+
+impl SpreadsheetFileType {
+    pub fn new(file: &[u8], options: SpreadsheetOptions) -> Result<Self, Box<dyn Error>> {
+        let mut workbook = open_workbook_auto_from_rs(Cursor::new(file))?;
+
+        let names = match &options.sheet {
+            Some(s) if workbook.sheet_names().contains(s) => vec![s.to_owned()],
+            Some(s) => return Err(format!("No sheet named {:?} in workbook", s).into()),
+            None => workbook.sheet_names(),
+        };
+
+        let mut sheets = Vec::new();
+
+        for name in names {
+            let range = workbook.worksheet_range(&name)?;
+            sheets.push((name, range_to_objects(&range)));
+        }
+
+        Ok(Self {
+            sheets,
+            combined: options.sheet.is_none(),
+        })
+    }
+}
+
+impl Filetype for SpreadsheetFileType {
+    fn to_object(self) -> DataValues {
+        let mut sheets = self.sheets.into_iter().map(|(name, objects)| {
+            let rows = objects
+                .into_iter()
+                .map(|o| {
+                    DataValues::Object(
+                        o.into_iter()
+                            .map(|(k, v)| (k, DataValues::Literal(v)))
+                            .collect(),
+                    )
+                })
+                .collect();
+
+            (name, DataValues::Array(rows))
+        });
+
+        // A single sheet reads as a table, as csv does, and a workbook as its tables by sheet name
+        if self.combined {
+            DataValues::Object(sheets.collect())
+        } else {
+            sheets
+                .next()
+                .map(|(_, rows)| rows)
+                .unwrap_or(DataValues::Array(Vec::new()))
+        }
+    }
+}
+
+/// Name of a spreadsheet column, as in `A`, `Z`, `AA`
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+
+        if index < 26 {
+            break;
+        }
+
+        index = index / 26 - 1;
+    }
+
+    name.reverse();
+    String::from_utf8(name).unwrap()
+}
+
+/// Reads the first row of a sheet as its header, and each row with any value after as a record
+fn range_to_objects(range: &Range<Data>) -> Vec<IndexMap<String, Literals>> {
+    let mut rows = range.rows();
+
+    let Some(header) = rows.next() else {
+        return Vec::new();
+    };
+
+    // Columns without a heading are named by their letter
+    let (_, column) = range.start().unwrap_or_default();
+    let fields = header
+        .iter()
+        .enumerate()
+        .map(|(i, cell)| match cell.to_string().trim() {
+            "" => column_name(column as usize + i),
+            h => h.to_owned(),
+        })
+        .collect::<Vec<_>>();
+
+    let rows = rows
+        .filter(|row| row.iter().any(|cell| !cell.is_empty()))
+        .collect::<Vec<_>>();
+    let temporals = (0..fields.len())
+        .map(|i| column_temporal(rows.iter().filter_map(|row| row.get(i))))
+        .collect::<Vec<_>>();
+
+    rows.into_iter()
+        .map(|row| {
+            fields
+                .iter()
+                .zip(row.iter().zip(&temporals))
+                .map(|(k, (v, t))| (k.to_owned(), cell_to_literal(v, *t)))
+                .collect()
+        })
+        .collect()
+}
+
+/// How the dates and times of a column are read
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Temporal {
+    Time,
+    Date,
+    DateTime,
+}
+
+/// Readers only tell dates apart from durations, not which parts a number format shows, so
+/// the values of a whole column decide it. A single cell can't, as a datetime at midnight
+/// looks like a date
+fn column_temporal<'a>(cells: impl Iterator<Item = &'a Data>) -> Temporal {
+    let days = cells
+        .filter_map(|cell| match cell {
+            Data::DateTime(d) if d.is_datetime() => Some(d.as_f64()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if days.iter().all(|d| *d < 1.0) {
+        Temporal::Time
+    } else if days.iter().all(|d| d.fract() == 0.0) {
+        Temporal::Date
+    } else {
+        Temporal::DateTime
+    }
+}
+
+/// Cells are typed already, so only text is looked at for formats. Workbooks have no time
+/// zones, which RFC 3339 times and date-times require, so only dates get a format and times
+/// and date-times are temporal
+fn cell_to_literal(cell: &Data, temporal: Temporal) -> Literals {
+    match cell {
+        Data::Empty | Data::Error(_) => Literals::Null,
+        Data::Bool(b) => Literals::Boolean(*b),
+        Data::Int(i) => Literals::Integer(*i),
+        // Workbooks keep every number as a float, whole ones are taken as integers
+        Data::Float(f) if f.fract() == 0.0 && f.abs() <= FLOAT_INTEGER_MAX => {
+            Literals::Integer(*f as i64)
+        }
+        Data::Float(f) => Literals::Float(f.to_bits()),
+        Data::String(s) => Literals::classify(s.to_owned()),
+        Data::DateTimeIso(s) => Literals::classify(s.to_owned()),
+        Data::DurationIso(s) => Literals::String(s.to_owned()),
+        Data::DateTime(d) => match d.as_datetime() {
+            Some(dt) if d.is_datetime() => match temporal {
+                Temporal::Time => Literals::Temporal(StringFormat::Time, dt.time().to_string()),
+                Temporal::Date => Literals::Formatted(StringFormat::Date, dt.date().to_string()),
+                Temporal::DateTime => Literals::Temporal(
+                    StringFormat::DateTime,
+                    format!("{}T{}", dt.date(), dt.time()),
+                ),
+            },
+            // Durations are a number of days
+            _ => Literals::Float(d.as_f64().to_bits()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Filetype;
+    use super::{SpreadsheetFileType as sft, SpreadsheetOptions, column_name};
+    use crate::filetype::JsonFileType;
+    use crate::state::DataValues as DV;
+    use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+
+    fn json(input: &str) -> DV {
+        JsonFileType::new(input).unwrap().to_object()
+    }
+
+    fn workbook() -> Vec<u8> {
+        let mut workbook = Workbook::new();
+        let date = Format::new().set_num_format("yyyy-mm-dd");
+        let datetime = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
+        let time = Format::new().set_num_format("hh:mm:ss");
+
+        let accounts = workbook.add_worksheet().set_name("Accounts").unwrap();
+        accounts
+            .write_row(0, 0, ["id", "name", "balance", "active"])
+            .unwrap();
+        accounts.write(1, 0, 1).unwrap();
+        accounts.write(1, 1, "Ann").unwrap();
+        accounts.write(1, 2, 10.5).unwrap();
+        accounts.write(1, 3, true).unwrap();
+        accounts.write(2, 0, 2).unwrap();
+        accounts.write(2, 1, "0042").unwrap();
+        accounts.write(2, 2, 3).unwrap();
+        accounts.write(2, 3, false).unwrap();
+
+        let payments = workbook.add_worksheet().set_name("Payments").unwrap();
+        payments.write(0, 0, "on").unwrap();
+        payments.write(0, 1, "at").unwrap();
+        payments
+            .write_datetime_with_format(1, 0, ExcelDateTime::from_ymd(2024, 1, 31).unwrap(), &date)
+            .unwrap();
+        payments
+            .write_datetime_with_format(
+                1,
+                1,
+                ExcelDateTime::parse_from_str("2024-01-31T10:30:00").unwrap(),
+                &datetime,
+            )
+            .unwrap();
+        payments.write(1, 2, "x").unwrap();
+        payments
+            .write_datetime_with_format(1, 3, ExcelDateTime::from_hms(10, 30, 0).unwrap(), &time)
+            .unwrap();
+        payments
+            .write_datetime_with_format(2, 0, ExcelDateTime::from_ymd(2024, 2, 1).unwrap(), &date)
+            .unwrap();
+        // Midnight in a column of datetimes is still a datetime
+        payments
+            .write_datetime_with_format(
+                2,
+                1,
+                ExcelDateTime::from_ymd(2024, 2, 1).unwrap(),
+                &datetime,
+            )
+            .unwrap();
+        payments
+            .write_datetime_with_format(2, 3, ExcelDateTime::from_hms(0, 0, 0).unwrap(), &time)
+            .unwrap();
+
+        workbook.save_to_buffer().unwrap()
+    }
+
+    #[test]
+    fn spreadsheet() {
+        let combined = sft::new(&workbook(), SpreadsheetOptions::new()).unwrap();
+        assert_eq!(
+            combined.to_object(),
+            json(
+                r#"{
+                    "Accounts": [
+                        {"id": 1, "name": "Ann", "balance": 10.5, "active": true},
+                        {"id": 2, "name": "0042", "balance": 3, "active": false}
+                    ],
+                    "Payments": [
+                        {"on": "2024-01-31", "at": "2024-01-31T10:30:00", "C": "x", "D": "10:30:00"},
+                        {"on": "2024-02-01", "at": "2024-02-01T00:00:00", "C": null, "D": "00:00:00"}
+                    ]
+                }"#
+            )
+        );
+
+        let mut options = SpreadsheetOptions::new();
+        options.sheet = Some(String::from("Payments"));
+
+        let sheet = sft::new(&workbook(), options).unwrap();
+        assert_eq!(
+            sheet.to_object(),
+            json(
+                r#"[
+                    {"on": "2024-01-31", "at": "2024-01-31T10:30:00", "C": "x", "D": "10:30:00"},
+                    {"on": "2024-02-01", "at": "2024-02-01T00:00:00", "C": null, "D": "00:00:00"}
+                ]"#
+            )
+        );
+
+        let mut options = SpreadsheetOptions::new();
+        options.sheet = Some(String::from("Missing"));
+
+        assert!(sft::new(&workbook(), options).is_err());
+        assert!(sft::new(b"id,name", SpreadsheetOptions::new()).is_err());
+    }
+
+    #[test]
+    fn columns() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(701), "ZZ");
+        assert_eq!(column_name(702), "AAA");
+    }
+}
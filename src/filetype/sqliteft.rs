@@ -0,0 +1,335 @@
+use super::Filetype;
+use crate::state::{DataValues, Literals};
+use indexmap::IndexMap;
+use rusqlite::{Connection, MAIN_DB, OpenFlags, types::ValueRef};
+use std::{error::Error, path::Path};
+
+// Bytes of the database header holding its file format versions, 2 when in WAL mode
+const WAL_VERSIONS: std::ops::Range<usize> = 18..20;
+
+type Rows = Vec<IndexMap<String, Literals>>;
+
+/// Columns having a declared type, with that type
+type Declared = Vec<(String, String)>;
+
+pub struct SqliteOptions {
+    pub table: Option<String>,
+}
+
+impl SqliteOptions {
+    pub fn new() -> Self {
+        Self { table: None }
+    }
+}
+
+pub struct SqliteFileType {
+    tables: Vec<(String, Rows)>,
+    declared: Vec<(String, Declared)>,
+    combined: bool,
+}
+
+impl SqliteFileType {
+    /// Opens a database file in place, so sqlite reads any changes still in its write ahead log
+    pub fn open(path: &Path, options: SqliteOptions) -> Result<Self, Box<dyn Error>> {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+
+        Self::read(&connection, options)
+    }
+
+    /// Reads a database from its bytes, as given on stdin
+    pub fn new(file: &[u8], options: SqliteOptions) -> Result<Self, Box<dyn Error>> {
+        let mut file = file.to_vec();
+
+        // Databases in memory can't use a write ahead log, so one is read as a plain database,
+        // without any changes still waiting in its log file
+        if file.get(WAL_VERSIONS) == Some(&[2, 2]) {
+            eprintln!(
+                "Warning: database is in WAL mode, changes not yet checkpointed are only read when given its path"
+            );
+            file[WAL_VERSIONS].fill(1);
+        }
+
+        let mut connection = Connection::open_in_memory()?;
+        connection.deserialize_read_exact(MAIN_DB, file.as_slice(), file.len(), true)?;
+
+        Self::read(&connection, options)
+    }
+
+    fn read(connection: &Connection, options: SqliteOptions) -> Result<Self, Box<dyn Error>> {
+        let names = {
+            let mut statement = connection.prepare(
+                "SELECT name, type = 'view' FROM sqlite_schema
+                 WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite\\_%' ESCAPE '\\'",
+            )?;
+
+            statement
+                .query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, bool>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        let names = match &options.table {
+            Some(t) if names.iter().any(|(n, _)| n == t) => vec![t.to_owned()],
+            Some(t) => return Err(format!("No table named {:?} in database", t).into()),
+            // Views only repeat what's in tables, so they're read when asked for by name
+            None => names
+                .into_iter()
+                .filter(|(_, view)| !view)
+                .map(|(n, _)| n)
+                .collect(),
+        };
+
+        let mut tables = Vec::new();
+        let mut declared = Vec::new();
+
+        for name in names {
+            let (columns, rows) = table_to_objects(connection, &name)?;
+            declared.push((name.clone(), columns));
+            tables.push((name, rows));
+        }
+
+        Ok(Self {
+            tables,
+            declared,
+            combined: options.table.is_none(),
+        })
+    }
+
+    /// Declared type of each column, by the keys leading to its values
+    pub fn declared_types(&self) -> Vec<(Vec<String>, String)> {
+        self.declared
+            .iter()
+            .flat_map(|(table, columns)| {
+                columns.iter().map(move |(column, declared)| {
+                    let keys = if self.combined {
+                        vec![table.to_owned(), column.to_owned()]
+                    } else {
+                        vec![column.to_owned()]
+                    };
+
+                    (keys, declared.to_owned())
+                })
+            })
+            .collect()
+    }
+}
+
+impl Filetype for SqliteFileType {
+    fn to_object(self) -> DataValues {
+        let mut tables = self.tables.into_iter().map(|(name, objects)| {
+            let rows = objects
+                .into_iter()
+                .map(|o| {
+                    DataValues::Object(
+                        o.into_iter()
+                            .map(|(k, v)| (k, DataValues::Literal(v)))
+                            .collect(),
+                    )
+                })
+                .collect();
+
+            (name, DataValues::Array(rows))
+        });
+
+        // A single table reads as csv does, and a database as its tables by name
+        if self.combined {
+            DataValues::Object(tables.collect())
+        } else {
+            tables
+                .next()
+                .map(|(_, rows)| rows)
+                .unwrap_or(DataValues::Array(Vec::new()))
+        }
+    }
+}
+
+/// Reads every row of a table, along with the columns having a declared type
+fn table_to_objects(
+    connection: &Connection,
+    table: &str,
+) -> Result<(Declared, Rows), Box<dyn Error>> {
+    let mut statement =
+        connection.prepare(&format!("SELECT * FROM \"{}\"", table.replace('"', "\"\"")))?;
+
+    // Views can repeat a column name, later ones are numbered as sqlite does in `CREATE TABLE AS`
+    let mut fields: Vec<String> = Vec::new();
+
+    for name in statement.column_names() {
+        let field = std::iter::once(name.to_owned())
+            .chain((1..).map(|i| format!("{}:{}", name, i)))
+            .find(|f| !fields.contains(f))
+            .unwrap();
+
+        fields.push(field);
+    }
+
+    // Types are declared by column position, views taking theirs from the columns they select
+    let declared = connection
+        .prepare("SELECT type FROM pragma_table_info(?1) ORDER BY cid")?
+        .query_map([table], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let columns = fields
+        .iter()
+        .zip(declared)
+        .filter(|(_, d)| !d.is_empty())
+        .map(|(f, d)| (f.to_owned(), d))
+        .collect();
+
+    let mut rows = statement.query([])?;
+    let mut objects = Vec::new();
+
+    while let Some(row) = rows.next()? {
+        let mut object = IndexMap::new();
+
+        for (i, field) in fields.iter().enumerate() {
+            object.insert(field.to_owned(), value_to_literal(row.get_ref(i)?));
+        }
+
+        objects.push(object);
+    }
+
+    Ok((columns, objects))
+}
+
+/// Values keep the type they were stored with, whatever their column was declared as. Text is
+/// coerced as csv fields are, numbers and flags often being kept as text
+fn value_to_literal(value: ValueRef) -> Literals {
+    match value {
+        ValueRef::Null => Literals::Null,
+        ValueRef::Integer(i) => Literals::Integer(i),
+        ValueRef::Real(f) => Literals::Float(f.to_bits()),
+        ValueRef::Text(t) => Literals::from(String::from_utf8_lossy(t).as_ref()),
+        // Binary data has no shape beyond being bytes, written as hex
+        ValueRef::Blob(b) => Literals::String(b.iter().map(|b| format!("{:02x}", b)).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Filetype;
+    use super::{SqliteFileType as sft, SqliteOptions};
+    use crate::filetype::JsonFileType;
+    use crate::state::DataValues as DV;
+    use rusqlite::{Connection, MAIN_DB};
+
+    fn json(input: &str) -> DV {
+        JsonFileType::new(input).unwrap().to_object()
+    }
+
+    fn database() -> Vec<u8> {
+        let connection = Connection::open_in_memory().unwrap();
+
+        connection
+            .execute_batch(
+                "CREATE TABLE users (id TEXT, name TEXT, score TEXT, active TEXT);
+                 INSERT INTO users VALUES ('1', 'Ann', '1.5', 'true'), ('2', 'Bo', NULL, 'no');
+                 CREATE TABLE \"event log\" (at TEXT, size INTEGER, data BLOB);
+                 INSERT INTO \"event log\" VALUES ('2024-01-01', 10, x'00ff');
+                 CREATE VIEW names AS SELECT name FROM users;
+                 CREATE VIEW pairs AS SELECT a.name, b.name, 1 FROM users a JOIN users b;",
+            )
+            .unwrap();
+
+        connection.serialize(MAIN_DB).unwrap().to_vec()
+    }
+
+    #[test]
+    fn sqlite() {
+        let combined = sft::new(&database(), SqliteOptions::new()).unwrap();
+        let declared = combined.declared_types();
+        assert_eq!(declared.len(), 7);
+        assert_eq!(
+            declared[4],
+            (
+                vec![String::from("event log"), String::from("at")],
+                String::from("TEXT")
+            )
+        );
+        assert_eq!(
+            declared[6],
+            (
+                vec![String::from("event log"), String::from("data")],
+                String::from("BLOB")
+            )
+        );
+
+        assert_eq!(
+            combined.to_object(),
+            json(
+                r#"{
+                    "users": [
+                        {"id": 1, "name": "Ann", "score": 1.5, "active": true},
+                        {"id": 2, "name": "Bo", "score": null, "active": false}
+                    ],
+                    "event log": [
+                        {"at": "2024-01-01", "size": 10, "data": "00ff"}
+                    ]
+                }"#
+            )
+        );
+
+        let mut options = SqliteOptions::new();
+        options.table = Some(String::from("names"));
+
+        let view = sft::new(&database(), options).unwrap();
+        assert_eq!(
+            view.to_object(),
+            json(r#"[{"name": "Ann"}, {"name": "Bo"}]"#)
+        );
+
+        // Repeated column names are numbered rather than overwritten
+        let mut options = SqliteOptions::new();
+        options.table = Some(String::from("pairs"));
+
+        let pairs = sft::new(&database(), options).unwrap();
+        assert_eq!(
+            pairs.declared_types(),
+            vec![
+                (vec![String::from("name")], String::from("TEXT")),
+                (vec![String::from("name:1")], String::from("TEXT"))
+            ]
+        );
+
+        let DV::Array(rows) = pairs.to_object() else {
+            panic!("Expected rows");
+        };
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0], json(r#"{"name": "Ann", "name:1": "Ann", "1": 1}"#));
+
+        let mut options = SqliteOptions::new();
+        options.table = Some(String::from("missing"));
+
+        assert!(sft::new(&database(), options).is_err());
+        assert!(sft::new(b"id,name", SqliteOptions::new()).is_err());
+    }
+
+    #[test]
+    fn wal() {
+        let path = std::env::temp_dir().join(format!("tabby-wal-{}.db", std::process::id()));
+        let writer = Connection::open(&path).unwrap();
+
+        writer
+            .execute_batch(
+                "PRAGMA journal_mode = WAL;
+                 CREATE TABLE users (id INTEGER, name TEXT);
+                 INSERT INTO users VALUES (1, 'Ann'), (2, 'Bo');",
+            )
+            .unwrap();
+
+        // Rows are still in the log while the writer is open
+        let database = sft::open(&path, SqliteOptions::new()).unwrap();
+        let rows = database.to_object();
+
+        drop(writer);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+
+        assert_eq!(
+            rows,
+            json(r#"{"users": [{"id": 1, "name": "Ann"}, {"id": 2, "name": "Bo"}]}"#)
+        );
+    }
+}
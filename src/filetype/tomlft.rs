@@ -0,0 +1,144 @@
+use super::Filetype;
+use crate::state::{DataValues, Literals, StringFormat};
+use std::error::Error;
+use toml::{Table, Value, value::Datetime};
+
+pub struct TomlFileType {
+    table: Table,
+}
+
+impl TomlFileType {
+    pub fn new(file: &str) -> Result<Self, Box<dyn Error>> {
+        let table = file.parse::<Table>()?;
+
+        Ok(Self { table })
+    }
+}
+
+impl Filetype for TomlFileType {
+    fn to_object(self) -> DataValues {
+        table_to_data(self.table)
+    }
+}
+
+fn table_to_data(table: Table) -> DataValues {
+    DataValues::Object(
+        table
+            .into_iter()
+            .map(|(k, v)| (k, toml_to_data(v)))
+            .collect(),
+    )
+}
+
+fn toml_to_data(value: Value) -> DataValues {
+    match value {
+        Value::String(s) => DataValues::Literal(Literals::classify(s)),
+        Value::Integer(i) => DataValues::Literal(Literals::Integer(i)),
+        Value::Float(f) => DataValues::Literal(Literals::Float(f.to_bits())),
+        Value::Boolean(b) => DataValues::Literal(Literals::Boolean(b)),
+        Value::Datetime(d) => DataValues::Literal(datetime_to_literal(d)),
+        Value::Array(a) => DataValues::Array(a.into_iter().map(toml_to_data).collect()),
+        Value::Table(t) => table_to_data(t),
+    }
+}
+
+/// Datetimes are already typed in toml, so take their kind from the parts they're made of.
+/// Local datetimes and times have no offset, which the RFC 3339 formats require, so they're
+/// temporal without a format
+fn datetime_to_literal(datetime: Datetime) -> Literals {
+    match (datetime.date, datetime.time, datetime.offset) {
+        (Some(_), Some(_), Some(_)) => {
+            Literals::Formatted(StringFormat::DateTime, datetime.to_string())
+        }
+        (Some(_), Some(_), None) => {
+            Literals::Temporal(StringFormat::DateTime, datetime.to_string())
+        }
+        (Some(_), None, _) => Literals::Formatted(StringFormat::Date, datetime.to_string()),
+        (None, _, _) => Literals::Temporal(StringFormat::Time, datetime.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Filetype;
+    use super::TomlFileType as tft;
+    use crate::filetype::JsonFileType;
+    use crate::state::{DataValues as DV, Literals as LT, StringFormat as SF};
+
+    #[test]
+    fn toml() {
+        let basic = tft::new(
+            r#"
+name = "tabby"
+version = "0.4.2"
+ratio = 0.5
+
+[dependencies]
+csv = "1.3.1"
+clap = { version = "4", features = ["derive"] }
+
+[[bin]]
+name = "a"
+
+[[bin]]
+name = "b"
+test = false
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            basic.to_object(),
+            JsonFileType::new(
+                r#"{
+                    "name": "tabby",
+                    "version": "0.4.2",
+                    "ratio": 0.5,
+                    "dependencies": {"csv": "1.3.1", "clap": {"version": "4", "features": ["derive"]}},
+                    "bin": [{"name": "a"}, {"name": "b", "test": false}]
+                }"#
+            )
+            .unwrap()
+            .to_object()
+        );
+
+        assert_eq!(
+            tft::new("").unwrap().to_object(),
+            DV::Object(Default::default())
+        );
+        assert!(tft::new("a = ").is_err());
+    }
+
+    #[test]
+    fn toml_datetimes() {
+        let dates = tft::new(
+            "offset = 1979-05-27T07:32:00Z
+local = 1979-05-27T07:32:00
+date = 1979-05-27
+time = 07:32:00
+text = \"1979-05-27\"",
+        )
+        .unwrap();
+
+        let DV::Object(dates) = dates.to_object() else {
+            panic!("Expected a table");
+        };
+
+        assert_eq!(
+            dates.into_values().collect::<Vec<_>>(),
+            vec![
+                DV::Literal(LT::Formatted(
+                    SF::DateTime,
+                    String::from("1979-05-27T07:32:00Z")
+                )),
+                DV::Literal(LT::Temporal(
+                    SF::DateTime,
+                    String::from("1979-05-27T07:32:00")
+                )),
+                DV::Literal(LT::Formatted(SF::Date, String::from("1979-05-27"))),
+                DV::Literal(LT::Temporal(SF::Time, String::from("07:32:00"))),
+                DV::Literal(LT::Formatted(SF::Date, String::from("1979-05-27")))
+            ]
+        );
+    }
+}
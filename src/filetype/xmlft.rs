@@ -0,0 +1,239 @@
+use super::Filetype;
+use crate::state::{DataValues, Literals};
+use indexmap::IndexMap;
+use roxmltree::{Document, Node, ParsingOptions};
+use std::{collections::HashSet, error::Error};
+
+/// Key holding the text of an element which also has attributes or child elements
+const TEXT_KEY: &str = "#text";
+
+pub struct XmlOptions {
+    pub attribute_prefix: String,
+}
+
+impl XmlOptions {
+    pub fn new() -> Self {
+        Self {
+            attribute_prefix: String::from("@"),
+        }
+    }
+}
+
+pub struct XmlFileType {
+    root: DataValues,
+}
+
+impl XmlFileType {
+    pub fn new(file: &str, options: XmlOptions) -> Result<Self, Box<dyn Error>> {
+        let parsing = ParsingOptions {
+            allow_dtd: true,
+            ..ParsingOptions::default()
+        };
+
+        let document = Document::parse_with_options(file, parsing)?;
+        let root = document.root_element();
+
+        let mut path = vec![element_name(root)];
+        let mut repeated = HashSet::new();
+        find_repeated(root, &mut path, &mut repeated);
+
+        let root = DataValues::Object(IndexMap::from([(
+            element_name(root),
+            element_to_data(root, &mut path, &repeated, &options),
+        )]));
+
+        Ok(Self { root })
+    }
+}
+
+impl Filetype for XmlFileType {
+    fn to_object(self) -> DataValues {
+        self.root
+    }
+}
+
+/// Names keep the prefix they were written with, so `dc:title` and `title` stay apart
+fn qualified_name(node: Node, namespace: Option<&str>, name: &str) -> String {
+    match namespace.and_then(|uri| node.lookup_prefix(uri)) {
+        Some(prefix) if !prefix.is_empty() => format!("{}:{}", prefix, name),
+        _ => name.to_owned(),
+    }
+}
+
+fn element_name(node: Node) -> String {
+    qualified_name(node, node.tag_name().namespace(), node.tag_name().name())
+}
+
+/// Paths, as the names of an element and its ancestors, of elements repeated among their
+/// siblings, which are lists wherever that path is found, however many there are there
+fn find_repeated(node: Node, path: &mut Vec<String>, repeated: &mut HashSet<Vec<String>>) {
+    let mut seen = HashSet::new();
+
+    for child in node.children().filter(|c| c.is_element()) {
+        path.push(element_name(child));
+
+        if !seen.insert(path.last().unwrap().clone()) {
+            repeated.insert(path.clone());
+        }
+
+        find_repeated(child, path, repeated);
+        path.pop();
+    }
+}
+
+fn element_to_data(
+    node: Node,
+    path: &mut Vec<String>,
+    repeated: &HashSet<Vec<String>>,
+    options: &XmlOptions,
+) -> DataValues {
+    let mut object: IndexMap<String, DataValues> = IndexMap::new();
+
+    for attribute in node.attributes() {
+        let name = qualified_name(node, attribute.namespace(), attribute.name());
+
+        object.insert(
+            format!("{}{}", options.attribute_prefix, name),
+            DataValues::Literal(Literals::from(attribute.value())),
+        );
+    }
+
+    let mut children: IndexMap<String, Vec<DataValues>> = IndexMap::new();
+    let mut segments = vec![String::new()];
+
+    for child in node.children() {
+        if child.is_element() {
+            path.push(element_name(child));
+            let value = element_to_data(child, path, repeated, options);
+
+            children.entry(path.pop().unwrap()).or_default().push(value);
+
+            // Text either side of an element is a separate run of words
+            segments.push(String::new());
+        } else if let Some(t) = child.text().filter(|_| child.is_text()) {
+            segments.last_mut().unwrap().push_str(t);
+        }
+    }
+
+    let text = segments
+        .iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    // Elements holding only text are taken as the value of that text
+    if object.is_empty() && children.is_empty() {
+        return DataValues::Literal(Literals::from(text.as_str()));
+    }
+
+    for (name, mut elements) in children {
+        path.push(name);
+
+        // Repeated elements are the items of a list
+        let value = if elements.len() == 1 && !repeated.contains(path) {
+            elements.pop().unwrap()
+        } else {
+            DataValues::Array(elements)
+        };
+
+        object.insert(path.pop().unwrap(), value);
+    }
+
+    if !text.is_empty() {
+        object.insert(
+            TEXT_KEY.to_owned(),
+            DataValues::Literal(Literals::from(text.as_str())),
+        );
+    }
+
+    DataValues::Object(object)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Filetype;
+    use super::{XmlFileType as xft, XmlOptions};
+    use crate::filetype::JsonFileType;
+    use crate::state::DataValues as DV;
+
+    fn json(input: &str) -> DV {
+        JsonFileType::new(input).unwrap().to_object()
+    }
+
+    #[test]
+    fn xml() {
+        const FEED: &str = r#"<?xml version="1.0"?>
+<feed xmlns:dc="http://purl.org/dc/elements/1.1/" version="2">
+  <!-- partner feed -->
+  <item id="1">
+    <title>First &amp; best</title>
+    <price currency="EUR">9.5</price>
+    <dc:date>2024-01-01</dc:date>
+    <tag>a</tag>
+    <tag>b</tag>
+  </item>
+  <item id="2">
+    <title><![CDATA[Second]]></title>
+    <price currency="EUR">12</price>
+    <active/>
+    <tag>c</tag>
+  </item>
+</feed>"#;
+
+        let feed = xft::new(FEED, XmlOptions::new()).unwrap();
+        assert_eq!(
+            feed.to_object(),
+            json(
+                r##"{"feed": {
+                    "@version": 2,
+                    "item": [
+                        {
+                            "@id": 1,
+                            "title": "First & best",
+                            "price": {"@currency": "EUR", "#text": 9.5},
+                            "dc:date": "2024-01-01",
+                            "tag": ["a", "b"]
+                        },
+                        {
+                            "@id": 2,
+                            "title": "Second",
+                            "price": {"@currency": "EUR", "#text": 12},
+                            "active": null,
+                            "tag": ["c"]
+                        }
+                    ]
+                }}"##
+            )
+        );
+
+        let mut options = XmlOptions::new();
+        options.attribute_prefix = String::from("_");
+
+        let prefixed = xft::new("<a b=\"yes\">x</a>", options).unwrap();
+        assert_eq!(
+            prefixed.to_object(),
+            json(r##"{"a": {"_b": true, "#text": "x"}}"##)
+        );
+
+        // Text around child elements keeps the words apart
+        let mixed = xft::new("<p>Hello <b>big</b>world<br/> !</p>", XmlOptions::new()).unwrap();
+        assert_eq!(
+            mixed.to_object(),
+            json(r##"{"p": {"b": "big", "br": null, "#text": "Hello world !"}}"##)
+        );
+
+        // Only elements repeated under the same path are lists
+        let nested = xft::new(
+            "<a><list><v>1</v><v>2</v></list><list><v>3</v></list><one><v>4</v></one></a>",
+            XmlOptions::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            nested.to_object(),
+            json(r#"{"a": {"list": [{"v": [1, 2]}, {"v": [3]}], "one": {"v": 4}}}"#)
+        );
+
+        assert!(xft::new("<a><b></a>", XmlOptions::new()).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+use super::Filetype;
+use crate::state::{DataValues, Literals};
+use serde::Deserialize;
+use serde_yaml_ng::{Deserializer, Value};
+use std::error::Error;
+
+pub struct YamlOptions {
+    /// Read the input as an array of documents even when it holds only one
+    pub documents: bool,
+}
+
+impl YamlOptions {
+    pub fn new() -> Self {
+        Self { documents: false }
+    }
+}
+
+pub struct YamlFileType {
+    documents: Vec<Value>,
+    stream: bool,
+}
+
+impl YamlFileType {
+    pub fn new(file: &str, options: YamlOptions) -> Result<Self, Box<dyn Error>> {
+        let mut documents: Vec<Value> = Vec::new();
+
+        for (number, document) in Deserializer::from_str(file).enumerate() {
+            let mut value = Value::deserialize(document)
+                .map_err(|e| format!("Document {}: {}", number + 1, e))?;
+
+            // Merge keys (`<<: *anchor`) are written out as the fields they stand for
+            value
+                .apply_merge()
+                .map_err(|e| format!("Document {}: {}", number + 1, e))?;
+
+            documents.push(value);
+        }
+
+        // A stream of several documents can only be read as an array of them
+        let stream = options.documents || documents.len() > 1;
+
+        Ok(Self { documents, stream })
+    }
+}
+
+impl Filetype for YamlFileType {
+    fn to_object(mut self) -> DataValues {
+        // A stream is an array of records, so its shape doesn't depend on how many it held
+        if self.stream {
+            return DataValues::Array(self.documents.into_iter().map(yaml_to_data).collect());
+        }
+
+        self.documents
+            .pop()
+            .map(yaml_to_data)
+            .unwrap_or(DataValues::Literal(Literals::Null))
+    }
+}
+
+fn yaml_to_data(value: Value) -> DataValues {
+    match value {
+        Value::Null => DataValues::Literal(Literals::Null),
+        Value::Bool(b) => DataValues::Literal(Literals::Boolean(b)),
+        Value::Number(n) => DataValues::Literal(match n.as_i64() {
+            Some(i) => Literals::Integer(i),
+            None => Literals::Float(n.as_f64().unwrap().to_bits()),
+        }),
+        Value::String(s) => DataValues::Literal(Literals::classify(s)),
+        Value::Sequence(s) => DataValues::Array(s.into_iter().map(yaml_to_data).collect()),
+        Value::Mapping(m) => DataValues::Object(
+            m.into_iter()
+                .map(|(k, v)| (key_to_string(k), yaml_to_data(v)))
+                .collect(),
+        ),
+        // Tags carry no shape of their own, only the value they're on
+        Value::Tagged(t) => yaml_to_data(t.value),
+    }
+}
+
+/// Mapping keys can be any value in yaml, but only strings are keys in the schema
+fn key_to_string(key: Value) -> String {
+    match key {
+        Value::String(s) => s,
+        Value::Null => String::from("null"),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        k => serde_yaml_ng::to_string(&k)
+            .map(|s| s.trim_end().to_owned())
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Filetype;
+    use super::{YamlFileType, YamlOptions};
+    use crate::filetype::JsonFileType;
+    use crate::state::{DataValues as DV, Literals as LT};
+
+    fn yft(input: &str, documents: bool) -> Result<YamlFileType, Box<dyn std::error::Error>> {
+        YamlFileType::new(input, YamlOptions { documents })
+    }
+
+    fn json(input: &str) -> DV {
+        JsonFileType::new(input).unwrap().to_object()
+    }
+
+    #[test]
+    fn yaml() {
+        assert_eq!(yft("", false).unwrap().to_object(), DV::Literal(LT::Null));
+        assert_eq!(yft("~", false).unwrap().to_object(), DV::Literal(LT::Null));
+        assert_eq!(
+            yft("'12'", false).unwrap().to_object(),
+            DV::Literal(LT::String(String::from("12")))
+        );
+
+        let basic = yft(
+            "name: app
+port: 8080
+ratio: 0.5
+debug: false
+tags: [a, b]
+owner: !user
+  id: 1
+1: one
+",
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            basic.to_object(),
+            json(
+                r#"{"name": "app", "port": 8080, "ratio": 0.5, "debug": false, "tags": ["a", "b"], "owner": {"id": 1}, "1": "one"}"#
+            )
+        );
+
+        let merged = yft(
+            "base: &base
+  a: 1
+derived:
+  <<: *base
+  b: 2
+",
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            merged.to_object(),
+            json(r#"{"base": {"a": 1}, "derived": {"b": 2, "a": 1}}"#)
+        );
+
+        assert!(yft("a: [1", false).is_err());
+    }
+
+    #[test]
+    fn yaml_documents() {
+        let stream = yft("---\na: 1\n---\na: 2\nb: x\n", true).unwrap();
+        assert_eq!(
+            stream.to_object(),
+            json(r#"[{"a": 1}, {"a": 2, "b": "x"}]"#)
+        );
+
+        // A stream of one document is still an array
+        let single = yft("---\na: 1\n", true).unwrap();
+        assert_eq!(single.to_object(), json(r#"[{"a": 1}]"#));
+
+        // Several documents are an array without asking for one
+        let implied = yft("---\na: 1\n---\na: 2\n", false).unwrap();
+        assert_eq!(implied.to_object(), json(r#"[{"a": 1}, {"a": 2}]"#));
+
+        let one = yft("---\na: 1\n", false).unwrap();
+        assert_eq!(one.to_object(), json(r#"{"a": 1}"#));
+
+        assert!(yft("a: 1\n---\na: [\n", true).is_err());
+    }
+}
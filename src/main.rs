@@ -2,14 +2,23 @@ mod codegen;
 mod filetype;
 mod state;
 
-use codegen::{CodegenOptions, Generation, JsonSchema};
-use filetype::{CsvFileType, CsvOptions, Filetype, JsonFileType};
+use codegen::{
+    Avro, CodegenOptions, Generation, Go, Graphql, JsonSchema, Proto, Python, Rust, Sql,
+    SqlDialect, TypeScript,
+};
+use filetype::{
+    CsvFileType, CsvOptions, CsvRecords, Filetype, JsonFileType, JsonRecords, JsonlFileType,
+    JsonlRecords, SpreadsheetFileType, SpreadsheetOptions, SqliteFileType, SqliteOptions,
+    TomlFileType, XmlFileType, XmlOptions, YamlFileType, YamlOptions,
+};
 use state::Subschema;
 
 use clap::{ArgAction, Parser, ValueEnum};
 use regex::Regex;
 use std::{
-    io::{IsTerminal, Read, Write},
+    cell::LazyCell,
+    fs::File,
+    io::{BufRead, BufReader, IsTerminal, Read, Write},
     path::PathBuf,
 };
 
@@ -26,6 +35,10 @@ pub struct Cli {
     #[arg(short = 'f', long = "input-format", value_enum)]
     input_format: Option<InputData>,
 
+    /// Output format (default: json-schema)
+    #[arg(short = 'F', long = "output-format", value_enum, default_value_t = OutputFormat::JsonSchema)]
+    output_format: OutputFormat,
+
     /// Output file path (default: stdout)
     #[arg(short = 'o', long = "output", value_name = "FILE")]
     output: Option<PathBuf>,
@@ -38,6 +51,26 @@ pub struct Cli {
     #[arg(long = "delimiter", value_name = "CHAR")]
     delimiter: Option<char>,
 
+    /// Optional prefix for keys taken from XML attributes (default: @)
+    #[arg(long = "attribute-prefix", value_name = "STRING")]
+    attribute_prefix: Option<String>,
+
+    /// Optional sheet of a spreadsheet to read as a table (default: every sheet, one `$defs` entry each)
+    #[arg(long = "sheet", value_name = "NAME")]
+    sheet: Option<String>,
+
+    /// Optional table or view of a SQLite database to read (default: every table, one `$defs` entry each)
+    #[arg(long = "table", value_name = "NAME")]
+    table: Option<String>,
+
+    /// Read YAML input as an array of documents even when it holds one (default: an array only for several)
+    #[arg(long = "documents", action = ArgAction::SetTrue)]
+    documents: bool,
+
+    /// Infer the schema record by record instead of loading the whole input (csv, jsonl, or a top-level json array)
+    #[arg(long = "stream", action = ArgAction::SetTrue)]
+    stream: bool,
+
     /// Disable use of `enum` keyword
     #[arg(long = "no-enum", action = ArgAction::SetFalse, default_value_t = true)]
     no_enum: bool,
@@ -46,6 +79,54 @@ pub struct Cli {
     #[arg(long = "no-const", action = ArgAction::SetFalse, default_value_t = true)]
     no_const: bool,
 
+    /// Disable use of `format` keyword
+    #[arg(long = "no-format", action = ArgAction::SetFalse, default_value_t = true)]
+    no_format: bool,
+
+    /// Enable `minimum` and `maximum` keywords from observed numeric values
+    #[arg(long = "range", action = ArgAction::SetTrue)]
+    range: bool,
+
+    /// With `--range`, give `exclusiveMinimum: 0` instead of `minimum` when all values are positive
+    #[arg(long = "exclusive-minimum", action = ArgAction::SetTrue, requires = "range")]
+    exclusive_minimum: bool,
+
+    /// Disable use of `prefixItems` for fixed length arrays with positional types
+    #[arg(long = "no-tuple", action = ArgAction::SetFalse, default_value_t = true)]
+    no_tuple: bool,
+
+    /// Enable `minLength` and `maxLength` keywords from observed string lengths
+    #[arg(long = "length", action = ArgAction::SetTrue)]
+    length: bool,
+
+    /// Enable `minItems`, `maxItems` and `uniqueItems` keywords from observed arrays
+    #[arg(long = "items", action = ArgAction::SetTrue)]
+    items: bool,
+
+    /// Disable describing dictionary-like objects with `additionalProperties` or `patternProperties`
+    #[arg(long = "no-map", action = ArgAction::SetFalse, default_value_t = true)]
+    no_map: bool,
+
+    /// Optional map minimum, number of distinct keys an object needs to be considered a dictionary
+    #[arg(long = "map-threshold", value_name = "COUNT")]
+    map_threshold: Option<usize>,
+
+    /// Disable splitting objects on a discriminator field into a `oneOf` of variants
+    #[arg(long = "no-variants", action = ArgAction::SetFalse, default_value_t = true)]
+    no_variants: bool,
+
+    /// Disable moving recursive and repeated structures into `$defs` referenced by `$ref`
+    #[arg(long = "no-defs", action = ArgAction::SetFalse, default_value_t = true)]
+    no_defs: bool,
+
+    /// With `--output-format python`, emit Pydantic models in place of dataclasses
+    #[arg(long = "pydantic", action = ArgAction::SetTrue)]
+    pydantic: bool,
+
+    /// With `--output-format sql`, database the `CREATE TABLE` statement is written for (default: postgres)
+    #[arg(long = "dialect", value_enum)]
+    dialect: Option<SqlDialect>,
+
     /// Optional enum percent, field must have less than given percent unique values to be counted as an enum
     #[arg(long = "enum-percent", value_name = "0-100")]
     enum_threshold: Option<u8>,
@@ -58,7 +139,14 @@ pub struct Cli {
 #[derive(Copy, Clone, Debug, ValueEnum)]
 pub enum InputData {
     Json,
+    Jsonl,
     Csv,
+    Yaml,
+    Toml,
+    Xml,
+    #[value(alias = "xlsx", alias = "ods")]
+    Spreadsheet,
+    Sqlite,
 }
 
 impl InputData {
@@ -73,7 +161,13 @@ impl InputData {
 
         let format = match &caps["ext"] {
             "json" => Some(Self::Json),
+            "jsonl" | "ndjson" => Some(Self::Jsonl),
             "csv" => Some(Self::Csv),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            "xml" => Some(Self::Xml),
+            "xlsx" | "xlsm" | "xlsb" | "xls" | "ods" => Some(Self::Spreadsheet),
+            "sqlite" | "sqlite3" | "db" => Some(Self::Sqlite),
             _ => None,
         };
 
@@ -81,6 +175,20 @@ impl InputData {
     }
 }
 
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum OutputFormat {
+    JsonSchema,
+    Rust,
+    Python,
+    Go,
+    Sql,
+    Avro,
+    Proto,
+    Graphql,
+    #[value(alias = "ts")]
+    Typescript,
+}
+
 fn resolve_title(cli: &Cli) -> Option<String> {
     if let Some(title) = &cli.title {
         Some(title.to_owned())
@@ -98,12 +206,12 @@ fn resolve_title(cli: &Cli) -> Option<String> {
     }
 }
 
-fn read_data(cli: &Cli) -> String {
+fn read_data(cli: &Cli) -> Vec<u8> {
     if let Some(file_path) = &cli.input {
-        std::fs::read_to_string(file_path)
+        std::fs::read(file_path)
             .unwrap_or_else(|_| panic!("Unable to open file: {}", &file_path.display()))
     } else {
-        let mut buffer = String::new();
+        let mut buffer = Vec::new();
         let mut stdin = std::io::stdin();
 
         if stdin.is_terminal() {
@@ -111,13 +219,30 @@ fn read_data(cli: &Cli) -> String {
         }
 
         stdin
-            .read_to_string(&mut buffer)
+            .read_to_end(&mut buffer)
             .expect("Unable to read from stdin");
 
         buffer
     }
 }
 
+fn open_data(cli: &Cli) -> Box<dyn BufRead> {
+    if let Some(file_path) = &cli.input {
+        let file = File::open(file_path)
+            .unwrap_or_else(|_| panic!("Unable to open file: {}", &file_path.display()));
+
+        Box::new(BufReader::new(file))
+    } else {
+        let stdin = std::io::stdin();
+
+        if stdin.is_terminal() {
+            panic!("No data input provided. Run `tabby --help` for usage.");
+        }
+
+        Box::new(stdin.lock())
+    }
+}
+
 fn resolve_format(cli: &Cli) -> InputData {
     let resolved = if let Some(input_format) = &cli.input_format {
         Some(*input_format)
@@ -137,28 +262,135 @@ fn resolve_format(cli: &Cli) -> InputData {
     resolved.unwrap_or(InputData::Json)
 }
 
-fn main() {
-    let cli = Cli::parse();
+fn csv_options(cli: &Cli) -> CsvOptions {
+    let mut csv_options = CsvOptions::new();
 
-    let title = resolve_title(&cli);
-    let file = read_data(&cli);
-    let file_format = resolve_format(&cli);
+    if let Some(delimiter) = cli.delimiter {
+        csv_options.delimiter = delimiter;
+    }
 
-    let input_data = match file_format {
-        InputData::Csv => {
-            let mut csv_options = CsvOptions::new();
+    csv_options
+}
 
-            if let Some(delimiter) = cli.delimiter {
-                csv_options.delimiter = delimiter;
-            }
+fn xml_options(cli: &Cli) -> XmlOptions {
+    let mut xml_options = XmlOptions::new();
 
-            CsvFileType::new(file.as_str(), csv_options)
-                .expect("Unable to parse csv")
-                .to_object()
-        }
-        InputData::Json => JsonFileType::new(file.as_str())
+    if let Some(prefix) = &cli.attribute_prefix {
+        xml_options.attribute_prefix = prefix.to_owned();
+    }
+
+    xml_options
+}
+
+fn yaml_options(cli: &Cli) -> YamlOptions {
+    let mut yaml_options = YamlOptions::new();
+
+    yaml_options.documents = cli.documents;
+
+    yaml_options
+}
+
+fn spreadsheet_options(cli: &Cli) -> SpreadsheetOptions {
+    let mut spreadsheet_options = SpreadsheetOptions::new();
+
+    spreadsheet_options.sheet = cli.sheet.to_owned();
+
+    spreadsheet_options
+}
+
+fn sqlite_options(cli: &Cli) -> SqliteOptions {
+    let mut sqlite_options = SqliteOptions::new();
+
+    sqlite_options.table = cli.table.to_owned();
+
+    sqlite_options
+}
+
+fn load_schema(cli: &Cli, file_format: InputData) -> Subschema {
+    // Databases given by path are opened in place rather than read
+    let file = LazyCell::new(|| read_data(cli));
+    let text = || std::str::from_utf8(&file).expect("Input is not valid UTF-8");
+    let mut declared = Vec::new();
+
+    let input_data = match file_format {
+        InputData::Csv => CsvFileType::new(text(), csv_options(cli))
+            .expect("Unable to parse csv")
+            .to_object(),
+        InputData::Json => JsonFileType::new(text())
             .expect("Unable to parse json")
             .to_object(),
+        InputData::Jsonl => JsonlFileType::new(text())
+            .expect("Unable to parse json lines")
+            .to_object(),
+        InputData::Yaml => YamlFileType::new(text(), yaml_options(cli))
+            .expect("Unable to parse yaml")
+            .to_object(),
+        InputData::Toml => TomlFileType::new(text())
+            .expect("Unable to parse toml")
+            .to_object(),
+        InputData::Xml => XmlFileType::new(text(), xml_options(cli))
+            .expect("Unable to parse xml")
+            .to_object(),
+        InputData::Spreadsheet => SpreadsheetFileType::new(&file, spreadsheet_options(cli))
+            .expect("Unable to read spreadsheet")
+            .to_object(),
+        InputData::Sqlite => {
+            let database = match &cli.input {
+                Some(path) => SqliteFileType::open(path, sqlite_options(cli)),
+                None => SqliteFileType::new(&file, sqlite_options(cli)),
+            }
+            .expect("Unable to read sqlite database");
+
+            declared = database.declared_types();
+            database.to_object()
+        }
+    };
+
+    let mut schema = Subschema::from_data(input_data);
+
+    for (keys, declared) in declared {
+        schema.declare(&keys, &declared);
+    }
+
+    schema
+}
+
+fn stream_schema(cli: &Cli, file_format: InputData) -> Subschema {
+    let input = open_data(cli);
+
+    match file_format {
+        InputData::Csv => Subschema::from_records(
+            CsvRecords::new(input, csv_options(cli)).expect("Unable to parse csv"),
+        )
+        .expect("Unable to parse csv"),
+        InputData::Json => {
+            Subschema::from_records(JsonRecords::new(input)).expect("Unable to parse json")
+        }
+        InputData::Jsonl => {
+            Subschema::from_records(JsonlRecords::new(input)).expect("Unable to parse json lines")
+        }
+        // Formats without a record by record reader are loaded whole, once the input is let go
+        InputData::Yaml
+        | InputData::Toml
+        | InputData::Xml
+        | InputData::Spreadsheet
+        | InputData::Sqlite => {
+            drop(input);
+            load_schema(cli, file_format)
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let title = resolve_title(&cli);
+    let file_format = resolve_format(&cli);
+
+    let schema = if cli.stream {
+        stream_schema(&cli, file_format)
+    } else {
+        load_schema(&cli, file_format)
     };
 
     let output_options = {
@@ -167,17 +399,51 @@ fn main() {
         options.title = title;
         options.use_enum = cli.no_enum;
         options.use_const = cli.no_const;
+        options.use_format = cli.no_format;
+        options.use_range = cli.range;
+        options.use_exclusive_minimum = cli.exclusive_minimum;
+        options.use_length = cli.length;
+        options.use_items = cli.items;
+        options.use_tuples = cli.no_tuple;
+        options.use_map = cli.no_map;
+        options.use_variants = cli.no_variants;
+        options.use_defs = cli.no_defs;
+        options.use_pydantic = cli.pydantic;
+        // Sheets of a workbook and tables of a database are each given a definition
+        options.use_property_defs = match file_format {
+            InputData::Spreadsheet => cli.sheet.is_none(),
+            InputData::Sqlite => cli.table.is_none(),
+            _ => false,
+        };
+
+        if let Some(n) = cli.map_threshold {
+            options.map_threshold = n;
+        };
 
         if let Some(n) = cli.enum_threshold {
             options.enum_threshold = n;
         };
 
+        if let Some(d) = cli.dialect {
+            options.sql_dialect = d;
+        };
+
         options.enum_maximum = cli.enum_maximum;
 
         options
     };
 
-    let output_code = JsonSchema::generate(Subschema::from_data(input_data), output_options);
+    let output_code = match cli.output_format {
+        OutputFormat::JsonSchema => JsonSchema::generate(schema, output_options),
+        OutputFormat::Rust => Rust::generate(schema, output_options),
+        OutputFormat::Python => Python::generate(schema, output_options),
+        OutputFormat::Go => Go::generate(schema, output_options),
+        OutputFormat::Sql => Sql::generate(schema, output_options),
+        OutputFormat::Avro => Avro::generate(schema, output_options),
+        OutputFormat::Proto => Proto::generate(schema, output_options),
+        OutputFormat::Graphql => Graphql::generate(schema, output_options),
+        OutputFormat::Typescript => TypeScript::generate(schema, output_options),
+    };
 
     match cli.output {
         Some(f) => {
@@ -1,5 +1,8 @@
 mod data;
 mod schema;
 
-pub use data::{DataValues, Literals};
-pub use schema::{ObjectProperty, Subschema, SubschemaTypes};
+pub use data::{DataValues, Literals, StringFormat};
+pub use schema::{
+    LengthRange, ObjectProperty, Subschema, SubschemaArray, SubschemaTypes, TaggedVariants,
+    crunch_schemas,
+};
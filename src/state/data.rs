@@ -1,6 +1,14 @@
+use indexmap::IndexMap;
 use regex::Regex;
 use serde_json::{Number, Value};
-use std::collections::HashMap;
+use std::{
+    borrow::Cow,
+    hash::{DefaultHasher, Hash, Hasher},
+    mem,
+    net::{Ipv4Addr, Ipv6Addr},
+    ops::Range,
+    sync::LazyLock,
+};
 
 const REMOVE_CHARS_PRE: [char; 2] = [' ', '$'];
 const REMOVE_CHARS_POST: [char; 3] = ['(', ')', '-'];
@@ -14,6 +22,133 @@ const PATTERNS_FLOAT_NEGATIVE: [&str; 2] = [
     r"^\([[:digit:]]+\.[[:digit:]]+\)$",
 ];
 
+// RFC 3339 full-date and full-time, which are what JSON Schema formats expect
+const PATTERN_DATE: &str = r"{year}-{month}-{day}";
+const PATTERN_TIME: &str = r"{hour}:{minute}:{second}(?:\.[[:digit:]]+)?(?:Z|[+-]{hour}:{minute})";
+
+// Common ways of writing dates and times short of RFC 3339, as `01/31/2024` or `2024-01-31 10:00`
+const PATTERNS_DATE_LOOSE: [&str; 4] = [
+    r"(?<year>{year})-(?<month>{month})-(?<day>{day})",
+    r"(?<year>{year})/(?<month>{month})/(?<day>{day})",
+    r"(?<month>{month})/(?<day>{day})/(?<year>{year})",
+    r"(?<day>{day})/(?<month>{month})/(?<year>{year})",
+];
+const PATTERN_TIME_LOOSE: &str =
+    r"{hour}:{minute}(?::{second}(?:\.[[:digit:]]+)?)?(?:Z|[+-]{hour}:?{minute})?";
+
+const PATTERN_UUID: &str =
+    r"^[[:xdigit:]]{8}-[[:xdigit:]]{4}-[[:xdigit:]]{4}-[[:xdigit:]]{4}-[[:xdigit:]]{12}$";
+const PATTERN_EMAIL: &str = r"^[[:alnum:].!#$%&'*+/=?^_`{|}~-]+@{hostname}$";
+const PATTERN_URI: &str = r"^(?:[[:alpha:]][[:alnum:]+.-]*://|(?i:mailto|urn|tel):)[^[:space:]]+$";
+// RFC 1123 labels, the last of them alphabetic as top level domains are
+const PATTERN_HOSTNAME: &str = r"^{hostname}$";
+
+/// Expands the component placeholders used by the format patterns
+fn format_pattern(pattern: &str) -> String {
+    pattern
+        .replace(
+            "{hostname}",
+            r"(?:[[:alnum:]](?:[[:alnum:]-]{0,61}[[:alnum:]])?\.)+[[:alpha:]]{2,63}",
+        )
+        .replace("{year}", r"[[:digit:]]{4}")
+        .replace("{month}", r"(?:0[1-9]|1[0-2])")
+        .replace("{day}", r"(?:0[1-9]|[12][[:digit:]]|3[01])")
+        .replace("{hour}", r"(?:[01][[:digit:]]|2[0-3])")
+        .replace("{minute}", r"[0-5][[:digit:]]")
+        .replace("{second}", r"[0-5][[:digit:]]")
+}
+
+static PATTERNS_FORMAT: LazyLock<Vec<(StringFormat, Regex)>> = LazyLock::new(|| {
+    [
+        (
+            StringFormat::DateTime,
+            format!("^{}T{}$", PATTERN_DATE, PATTERN_TIME),
+        ),
+        (StringFormat::Date, format!("^{}$", PATTERN_DATE)),
+        (StringFormat::Time, format!("^{}$", PATTERN_TIME)),
+        (StringFormat::Uuid, PATTERN_UUID.to_owned()),
+        (StringFormat::Email, PATTERN_EMAIL.to_owned()),
+        (StringFormat::Uri, PATTERN_URI.to_owned()),
+        (StringFormat::Hostname, PATTERN_HOSTNAME.to_owned()),
+    ]
+    .into_iter()
+    .map(|(f, p)| (f, Regex::new(&format_pattern(&p)).unwrap()))
+    .collect()
+});
+
+static PATTERNS_TEMPORAL: LazyLock<Vec<(StringFormat, Regex)>> = LazyLock::new(|| {
+    PATTERNS_DATE_LOOSE
+        .into_iter()
+        .flat_map(|date| {
+            [
+                (
+                    StringFormat::DateTime,
+                    format!("^{}[T ]{}$", date, PATTERN_TIME_LOOSE),
+                ),
+                (StringFormat::Date, format!("^{}$", date)),
+            ]
+        })
+        .chain([(StringFormat::Time, format!("^{}$", PATTERN_TIME_LOOSE))])
+        .map(|(f, p)| (f, Regex::new(&format_pattern(&p)).unwrap()))
+        .collect()
+});
+
+/// Checks that a string starting with a full-date names a day that exists
+fn check_calendar(data: &str) -> bool {
+    let part = |range: Range<usize>| data[range].parse::<u32>().unwrap();
+
+    check_day(part(0..4), part(5..7), part(8..10))
+}
+
+fn check_day(year: u32, month: u32, day: u32) -> bool {
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    let days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+
+    day <= days
+}
+
+// Longest hostname allowed by RFC 1123, also used as a bound for whole email addresses
+const HOSTNAME_MAX_LENGTH: usize = 253;
+
+fn check_format(data: &str) -> Option<StringFormat> {
+    if data.parse::<Ipv4Addr>().is_ok() {
+        return Some(StringFormat::Ipv4);
+    }
+
+    if data.parse::<Ipv6Addr>().is_ok() {
+        return Some(StringFormat::Ipv6);
+    }
+
+    PATTERNS_FORMAT
+        .iter()
+        .find(|(_, re)| re.is_match(data))
+        .map(|(f, _)| *f)
+        .filter(|f| match f {
+            StringFormat::Hostname => data.len() <= HOSTNAME_MAX_LENGTH,
+            StringFormat::Email => data.len() <= HOSTNAME_MAX_LENGTH,
+            StringFormat::DateTime | StringFormat::Date => check_calendar(data),
+            _ => true,
+        })
+}
+
+/// Kind of date or time a string written in a common, not strictly RFC 3339, way holds
+fn check_temporal(data: &str) -> Option<StringFormat> {
+    PATTERNS_TEMPORAL.iter().find_map(|(f, re)| {
+        let caps = re.captures(data)?;
+        let part = |name: &str| caps.name(name).map(|m| m.as_str().parse::<u32>().unwrap());
+
+        match (part("year"), part("month"), part("day")) {
+            (Some(year), Some(month), Some(day)) if !check_day(year, month, day) => None,
+            _ => Some(*f),
+        }
+    })
+}
+
 fn check_integer(data: &str) -> Option<i64> {
     let data = data.replace(REMOVE_CHARS_PRE, "");
 
@@ -86,6 +221,36 @@ fn refine_number(num: Number) -> Literals {
     }
 }
 
+/// Well known string shapes, named after the JSON Schema `format` vocabulary
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum StringFormat {
+    DateTime,
+    Date,
+    Time,
+    Email,
+    Uri,
+    Uuid,
+    Ipv4,
+    Ipv6,
+    Hostname,
+}
+
+impl StringFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::DateTime => "date-time",
+            Self::Date => "date",
+            Self::Time => "time",
+            Self::Email => "email",
+            Self::Uri => "uri",
+            Self::Uuid => "uuid",
+            Self::Ipv4 => "ipv4",
+            Self::Ipv6 => "ipv6",
+            Self::Hostname => "hostname",
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Literals {
     Null,
@@ -93,6 +258,40 @@ pub enum Literals {
     Integer(i64),
     Float(u64),
     String(String),
+    Formatted(StringFormat, String),
+    /// Date, time or date-time written in a common way other than RFC 3339, so with no `format`
+    Temporal(StringFormat, String),
+}
+
+impl Literals {
+    /// Classifies a value already known to be a string, without coercing it to other types
+    pub fn classify(data: String) -> Self {
+        let trimmed = data.trim();
+
+        if let Some(f) = check_format(trimmed) {
+            Self::Formatted(f, data)
+        } else if let Some(t) = check_temporal(trimmed) {
+            Self::Temporal(t, data)
+        } else {
+            Self::String(data)
+        }
+    }
+
+    /// Floats holding a whole number as the integer they equal, as JSON compares numbers by value
+    fn normalized(&self) -> Cow<'_, Self> {
+        match self {
+            Self::Float(bits) => {
+                let f = f64::from_bits(*bits);
+
+                if f.fract() == 0.0 && f >= i64::MIN as f64 && f < i64::MAX as f64 {
+                    Cow::Owned(Self::Integer(f as i64))
+                } else {
+                    Cow::Borrowed(self)
+                }
+            }
+            _ => Cow::Borrowed(self),
+        }
+    }
 }
 
 impl From<&str> for Literals {
@@ -123,15 +322,54 @@ impl From<&str> for Literals {
             return Self::Float(f.to_bits());
         }
 
-        Self::String(data.to_owned())
+        Self::classify(data.to_owned())
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug)]
 pub enum DataValues {
     Literal(Literals),
     Array(Vec<DataValues>),
-    Object(HashMap<String, DataValues>),
+    Object(IndexMap<String, DataValues>),
+}
+
+/// Numbers compare by value, so `1` and `1.0` are the same value
+impl PartialEq for DataValues {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Literal(a), Self::Literal(b)) => a.normalized() == b.normalized(),
+            (Self::Array(a), Self::Array(b)) => a == b,
+            (Self::Object(a), Self::Object(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for DataValues {}
+
+impl Hash for DataValues {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+
+        match self {
+            Self::Literal(l) => l.normalized().hash(state),
+            Self::Array(a) => a.hash(state),
+            Self::Object(o) => {
+                // Maps have no fixed iteration order, so entries are combined order independently
+                let entries = o
+                    .iter()
+                    .map(|entry| {
+                        let mut hasher = DefaultHasher::new();
+                        entry.hash(&mut hasher);
+                        hasher.finish()
+                    })
+                    .fold(0u64, u64::wrapping_add);
+
+                o.len().hash(state);
+                entries.hash(state);
+            }
+        }
+    }
 }
 
 impl From<Value> for DataValues {
@@ -140,7 +378,7 @@ impl From<Value> for DataValues {
             Value::Null => Self::Literal(Literals::Null),
             Value::Bool(b) => Self::Literal(Literals::Boolean(b)),
             Value::Number(n) => Self::Literal(refine_number(n)),
-            Value::String(s) => Self::Literal(Literals::String(s)),
+            Value::String(s) => Self::Literal(Literals::classify(s)),
             Value::Array(a) => Self::Array(a.into_iter().map(Self::from).collect()),
             Value::Object(m) => {
                 Self::Object(m.into_iter().map(|(k, v)| (k, Self::from(v))).collect())
@@ -245,4 +483,143 @@ mod tests {
             assert_eq!(Literals::from(test), Literals::String(test.to_owned()));
         }
     }
+
+    #[test]
+    fn temporal_parsing() {
+        const FORMATS: [(&str, StringFormat); 8] = [
+            ("2024-01-31T10:00:00Z", StringFormat::DateTime),
+            ("2024-01-31T10:00:00.123+05:30", StringFormat::DateTime),
+            ("2000-02-29T23:59:59-08:00", StringFormat::DateTime),
+            ("2024-01-31", StringFormat::Date),
+            (" 2024-02-29 ", StringFormat::Date),
+            ("10:00:00Z", StringFormat::Time),
+            ("00:00:00.5-01:00", StringFormat::Time),
+            ("23:59:59+01:00", StringFormat::Time),
+        ];
+
+        for (input, format) in FORMATS {
+            assert_eq!(
+                Literals::from(input),
+                Literals::Formatted(format, input.to_owned())
+            );
+        }
+
+        // Common variants are temporal, without a format
+        const TEMPORALS: [(&str, StringFormat); 10] = [
+            ("31/01/2024", StringFormat::Date),
+            ("01/31/2024", StringFormat::Date),
+            ("2024/01/31", StringFormat::Date),
+            ("2024-01-31 10:00", StringFormat::DateTime),
+            ("01/31/2024 10:00:00", StringFormat::DateTime),
+            ("2024-01-31T10:00:00", StringFormat::DateTime),
+            ("2024-01-31T10:00:00+0800", StringFormat::DateTime),
+            ("2024-01-31 10:00:00.5Z", StringFormat::DateTime),
+            ("10:00", StringFormat::Time),
+            ("23:59:59", StringFormat::Time),
+        ];
+
+        for (input, kind) in TEMPORALS {
+            assert_eq!(
+                Literals::from(input),
+                Literals::Temporal(kind, input.to_owned()),
+                "{:?}",
+                input
+            );
+        }
+
+        const STRS: [&str; 13] = [
+            "2024-13-01",
+            "2024-01-32",
+            "2024-02-30",
+            "2023-02-29",
+            "1900-02-29T10:00:00Z",
+            "2024-04-31",
+            "02/30/2024",
+            "13/13/2024",
+            "2024-01-31T10",
+            "2024-01-31 10:00 am",
+            "on 2024-01-31",
+            "24:00:00Z",
+            "10:60",
+        ];
+
+        for test in STRS {
+            assert_eq!(Literals::from(test), Literals::String(test.to_owned()));
+        }
+    }
+
+    #[test]
+    fn format_parsing() {
+        const FORMATS: [(&str, StringFormat); 19] = [
+            ("user@example.com", StringFormat::Email),
+            ("first.last+tag@mail.example.co.uk", StringFormat::Email),
+            ("https://example.com/a?b=c#d", StringFormat::Uri),
+            ("ftp://files.example.com", StringFormat::Uri),
+            ("mailto:user@example.com", StringFormat::Uri),
+            ("urn:isbn:0451450523", StringFormat::Uri),
+            ("123e4567-e89b-12d3-a456-426614174000", StringFormat::Uuid),
+            ("123E4567-E89B-12D3-A456-426614174000", StringFormat::Uuid),
+            ("192.168.0.1", StringFormat::Ipv4),
+            ("::1", StringFormat::Ipv6),
+            ("2001:db8::8a2e:370:7334", StringFormat::Ipv6),
+            ("example.com", StringFormat::Hostname),
+            ("api-v2.internal.example.org", StringFormat::Hostname),
+            (" www.example.com ", StringFormat::Hostname),
+            ("bbc.co.uk", StringFormat::Hostname),
+            ("www.example.de", StringFormat::Hostname),
+            ("example.de", StringFormat::Hostname),
+            ("foo.app", StringFormat::Hostname),
+            ("api.dev", StringFormat::Hostname),
+        ];
+
+        for (input, format) in FORMATS {
+            assert_eq!(
+                Literals::from(input),
+                Literals::Formatted(format, input.to_owned()),
+                "{:?}",
+                input
+            );
+        }
+
+        const STRS: [&str; 15] = [
+            "user@",
+            "@example.com",
+            "user@localhost",
+            "http:/example.com",
+            "123e4567-e89b-12d3-a456-42661417400",
+            "256.1.1.1",
+            "1.2.3",
+            "2001:db8:::1",
+            "-example.com",
+            "example.c0m",
+            "example.",
+            "example..com",
+            "under_score.com",
+            "example-.com",
+            "example.c",
+        ];
+
+        for test in STRS {
+            assert_eq!(Literals::from(test), Literals::String(test.to_owned()));
+        }
+    }
+
+    #[test]
+    fn json_strings() {
+        assert_eq!(
+            DataValues::from(Value::from("2024-01-31")),
+            DataValues::Literal(Literals::Formatted(
+                StringFormat::Date,
+                String::from("2024-01-31")
+            ))
+        );
+
+        // Json strings keep their type, even if they look like other literals
+        for test in ["12", "true", "null"] {
+            assert_eq!(
+                DataValues::from(Value::from(test)),
+                DataValues::Literal(Literals::String(test.to_owned()))
+            );
+        }
+    }
 }
@@ -1,23 +1,347 @@
-use super::{DataValues, Literals};
-use std::collections::{HashMap, HashSet};
+use super::{DataValues, Literals, StringFormat};
+use indexmap::IndexMap;
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+};
 
-#[derive(PartialEq, Eq, Debug)]
+// Longest array whose positions are tracked separately
+const POSITIONS_MAX_LENGTH: usize = 16;
+
+// Most distinct values kept for a subschema, past the largest enum that can be asked for
+const VALUES_MAX_COUNT: usize = 256;
+
+/// Conventional names of fields telling apart the kinds of object sharing one stream, preferred
+/// in this order when more than one field splits the objects equally well
+const DISCRIMINATOR_KEYS: [&str; 8] = [
+    "type",
+    "kind",
+    "event_type",
+    "eventType",
+    "variant",
+    "@type",
+    "_type",
+    "__typename",
+];
+
+// Most distinct values a discriminator can take before it's no longer tracked
+const VARIANTS_MAX_COUNT: usize = 32;
+
+/// Objects split on the string value of their discriminator key
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TaggedVariants {
+    pub key: String,
+    /// Schema of the objects carrying each value, in the order the values were first seen
+    pub values: IndexMap<String, Subschema>,
+}
+
+/// Keys carried by the objects holding one value of a candidate discriminator
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TagShape {
+    pub count: usize,
+    /// Number of those objects known to carry each key, which only has to tell whether all did
+    pub keys: IndexMap<String, usize>,
+}
+
+impl TagShape {
+    fn new() -> Self {
+        Self {
+            count: 0,
+            keys: IndexMap::new(),
+        }
+    }
+
+    fn add(&mut self, count: usize, keys: &mut dyn Iterator<Item = (&String, usize)>) {
+        for (k, n) in keys {
+            match self.keys.get_mut(k) {
+                Some(m) => *m += n,
+                None => {
+                    self.keys.insert(k.to_owned(), n);
+                }
+            }
+        }
+
+        self.count += count;
+    }
+
+    /// Whether any of the given keys is new to these objects
+    fn lacks(&self, keys: &mut dyn Iterator<Item = (&String, usize)>) -> bool {
+        for (k, _) in keys {
+            if !self.keys.contains_key(k) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn same_keys(&self, other: &Self) -> bool {
+        self.keys.len() == other.keys.len() && self.keys.keys().all(|k| other.keys.contains_key(k))
+    }
+
+    /// Keys some of the objects went without
+    fn optional(&self) -> usize {
+        self.keys.values().filter(|n| **n < self.count).count()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Tags {
+    /// Every object carried this value, so the object schema tells their keys
+    Single(String),
+    /// Keys carried alongside each value, in the order the values were first seen
+    Shapes(IndexMap<String, TagShape>),
+}
+
+impl Tags {
+    /// Calls `f` with each value, the number of objects carrying it, and their keys. Keys some
+    /// objects of a single value went without are counted for none
+    fn each<'a>(
+        &'a self,
+        sb: &'a Subschema,
+        mut f: impl FnMut(&'a str, usize, &mut dyn Iterator<Item = (&'a String, usize)>),
+    ) {
+        match self {
+            Tags::Single(tag) => {
+                let mut keys = sb.object.iter().flatten().map(|(k, p)| match p.required {
+                    true => (k, sb.object_count),
+                    false => (k, 0),
+                });
+
+                f(tag, sb.object_count, &mut keys)
+            }
+            Tags::Shapes(shapes) => {
+                for (tag, shape) in shapes {
+                    f(
+                        tag,
+                        shape.count,
+                        &mut shape.keys.iter().map(|(k, n)| (k, *n)),
+                    )
+                }
+            }
+        }
+    }
+
+    fn into_shapes(self, sb: &Subschema) -> IndexMap<String, TagShape> {
+        match self {
+            Tags::Shapes(shapes) => shapes,
+            single => {
+                let mut shapes = IndexMap::new();
+                single.each(sb, |tag, count, keys| {
+                    add_shape(&mut shapes, tag, count, keys)
+                });
+                shapes
+            }
+        }
+    }
+}
+
+fn add_shape(
+    shapes: &mut IndexMap<String, TagShape>,
+    tag: &str,
+    count: usize,
+    keys: &mut dyn Iterator<Item = (&String, usize)>,
+) {
+    match shapes.get_mut(tag) {
+        Some(shape) => shape.add(count, keys),
+        None => {
+            let mut shape = TagShape::new();
+            shape.add(count, keys);
+            shapes.insert(tag.to_owned(), shape);
+        }
+    }
+}
+
+/// String property every observed object carried, which could tell kinds of object apart
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Candidate {
+    pub key: String,
+    pub tags: Tags,
+    /// Schema of the objects carrying each value, only kept while values come with different keys
+    pub split: Option<IndexMap<String, Subschema>>,
+}
+
+impl Candidate {
+    fn values(&self) -> usize {
+        match &self.tags {
+            Tags::Single(_) => 1,
+            Tags::Shapes(shapes) => shapes.len(),
+        }
+    }
+
+    /// Objects carrying different values came with different keys
+    fn splits(&self) -> bool {
+        match &self.tags {
+            Tags::Single(_) => false,
+            Tags::Shapes(shapes) => shapes.values().any(|s| !s.same_keys(&shapes[0])),
+        }
+    }
+
+    /// A variant per value, and every key left optional within one
+    fn cost(&self) -> usize {
+        match &self.tags {
+            Tags::Single(_) => 1,
+            Tags::Shapes(shapes) => {
+                shapes.len() + shapes.values().map(TagShape::optional).sum::<usize>()
+            }
+        }
+    }
+}
+
+/// Smallest and largest length observed
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LengthRange {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl LengthRange {
+    pub fn new(length: usize) -> Self {
+        Self {
+            min: length,
+            max: length,
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+}
+
+fn merge_lengths(l1: Option<LengthRange>, l2: Option<LengthRange>) -> Option<LengthRange> {
+    match (l1, l2) {
+        (Some(l1), Some(l2)) => Some(l1.merge(l2)),
+        (l1, l2) => l1.or(l2),
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct SubschemaTypes {
     pub values: HashSet<Literals>,
     pub instance_count: usize,
+    /// Lengths of string values, in unicode scalar values
+    pub string_lengths: Option<LengthRange>,
+    /// More distinct values were seen than are kept, so `values` only stands in for them
+    pub overflowed: bool,
+    /// Type the source declared the values as, for sources with a schema of their own
+    pub declared: Option<String>,
 }
 
-#[derive(PartialEq, Eq, Debug)]
+impl SubschemaTypes {
+    fn merge(self, other: Self) -> Self {
+        let mut values = self.values;
+        values.extend(other.values);
+
+        let overflowed = self.overflowed || other.overflowed || values.len() > VALUES_MAX_COUNT;
+
+        Self {
+            values: if overflowed {
+                summarize(values)
+            } else {
+                values
+            },
+            instance_count: self.instance_count + other.instance_count,
+            string_lengths: merge_lengths(self.string_lengths, other.string_lengths),
+            overflowed,
+            declared: self.declared.or(other.declared),
+        }
+    }
+}
+
+/// Values standing in for too many to keep: nulls and booleans, the extremes of each kind of
+/// number and a string of each format, enough for types, ranges and formats
+fn summarize(values: HashSet<Literals>) -> HashSet<Literals> {
+    let mut kept = HashSet::new();
+    let mut integers: Option<(i64, i64)> = None;
+    let mut floats: Option<(f64, f64)> = None;
+    // Strings by format, and by kind of date or time for those without one
+    let mut strings: HashMap<(Option<StringFormat>, bool), Literals> = HashMap::new();
+
+    for value in values {
+        match value {
+            Literals::Integer(i) => {
+                integers = Some(integers.map_or((i, i), |(min, max)| (min.min(i), max.max(i))));
+            }
+            Literals::Float(f) => {
+                let f = f64::from_bits(f);
+                floats = Some(floats.map_or((f, f), |(min, max)| (min.min(f), max.max(f))));
+            }
+            Literals::String(_) => {
+                strings.entry((None, false)).or_insert(value);
+            }
+            Literals::Formatted(format, _) => {
+                strings.entry((Some(format), false)).or_insert(value);
+            }
+            Literals::Temporal(kind, _) => {
+                strings.entry((Some(kind), true)).or_insert(value);
+            }
+            Literals::Null | Literals::Boolean(_) => {
+                kept.insert(value);
+            }
+        }
+    }
+
+    if let Some((min, max)) = integers {
+        kept.extend([Literals::Integer(min), Literals::Integer(max)]);
+    }
+
+    if let Some((min, max)) = floats {
+        kept.extend([
+            Literals::Float(min.to_bits()),
+            Literals::Float(max.to_bits()),
+        ]);
+    }
+
+    kept.extend(strings.into_values());
+    kept
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct ObjectProperty {
     pub value: Subschema,
     pub required: bool,
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SubschemaArray {
+    /// Schema of every element, left empty while positions are kept
+    pub items: Box<Subschema>,
+    /// Schema of each position, kept while every observed array has the same short length
+    pub positions: Option<Vec<Subschema>>,
+    pub lengths: LengthRange,
+    /// No observed array held the same element twice
+    pub unique: bool,
+    pub instance_count: usize,
+}
+
+impl SubschemaArray {
+    /// Schema of every element, folded from the positions while those are kept
+    pub fn into_items(self) -> Subschema {
+        match self.positions {
+            Some(positions) => positions
+                .into_iter()
+                .reduce(crunch_schemas)
+                .unwrap_or(Subschema::new()),
+            None => *self.items,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub struct Subschema {
     pub types: Option<SubschemaTypes>,
-    pub array: Option<Box<Subschema>>,
-    pub object: Option<HashMap<String, ObjectProperty>>,
+    pub array: Option<SubschemaArray>,
+    /// Properties in the order their keys were first seen
+    pub object: Option<IndexMap<String, ObjectProperty>>,
+    /// Number of objects observed
+    pub object_count: usize,
+    /// Discriminator chosen among the candidates once every value is read
+    pub variants: Option<TaggedVariants>,
+    /// Discriminators the observed objects could be split on, until one is chosen
+    pub candidates: Vec<Candidate>,
 }
 
 impl Subschema {
@@ -26,6 +350,87 @@ impl Subschema {
             types: None,
             array: None,
             object: None,
+            object_count: 0,
+            variants: None,
+            candidates: Vec::new(),
+        }
+    }
+
+    /// Folds a stream of records into the schema of an array holding them, one record at a time.
+    /// Records are not retained, so the array is never considered unique.
+    pub fn from_records<I, E>(records: I) -> Result<Self, E>
+    where
+        I: IntoIterator<Item = Result<DataValues, E>>,
+    {
+        let mut items: Option<Self> = None;
+        let mut count: usize = 0;
+
+        for record in records {
+            let record = Self::from_data(record?);
+            count += 1;
+
+            items = Some(match items {
+                Some(i) => crunch_schemas(i, record),
+                None => record,
+            });
+        }
+
+        Ok(Self {
+            types: None,
+            array: Some(SubschemaArray {
+                items: Box::new(items.unwrap_or(Self::new())),
+                positions: None,
+                lengths: LengthRange::new(count),
+                unique: false,
+                instance_count: 1,
+            }),
+            object: None,
+            object_count: 0,
+            variants: None,
+            candidates: Vec::new(),
+        })
+    }
+
+    /// Chooses the discriminator among the candidates, once every object has been merged. Only
+    /// keys whose values come with different keys, some value being carried more than once, split
+    /// anything. The one costing the least wins, conventional names breaking ties before the order
+    /// keys were first seen in
+    pub fn choose_variants(&mut self) {
+        let object_count = self.object_count;
+
+        self.variants = mem::take(&mut self.candidates)
+            .into_iter()
+            .filter(|c| c.values() < object_count && c.splits())
+            .filter_map(|c| Some((c.cost(), c.key, c.split?)))
+            .min_by_key(|(cost, key, _)| {
+                let conventional = DISCRIMINATOR_KEYS.iter().position(|k| k == key);
+                (*cost, conventional.unwrap_or(DISCRIMINATOR_KEYS.len()))
+            })
+            .map(|(_, key, values)| TaggedVariants { key, values });
+    }
+
+    /// Notes the type a source declared for the values found under `keys`, through any arrays
+    /// on the way
+    pub fn declare(&mut self, keys: &[String], declared: &str) {
+        if let Some(a) = &mut self.array {
+            a.items.declare(keys, declared);
+
+            for p in a.positions.iter_mut().flatten() {
+                p.declare(keys, declared);
+            }
+        }
+
+        match keys.split_first() {
+            None => {
+                if let Some(t) = &mut self.types {
+                    t.declared = Some(declared.to_owned());
+                }
+            }
+            Some((key, rest)) => {
+                if let Some(p) = self.object.as_mut().and_then(|o| o.get_mut(key)) {
+                    p.value.declare(rest, declared);
+                }
+            }
         }
     }
 
@@ -33,60 +438,272 @@ impl Subschema {
         match data {
             DataValues::Literal(t) => Self {
                 types: Some(SubschemaTypes {
+                    string_lengths: match &t {
+                        Literals::String(s)
+                        | Literals::Formatted(_, s)
+                        | Literals::Temporal(_, s) => Some(LengthRange::new(s.chars().count())),
+                        _ => None,
+                    },
                     values: HashSet::from([t]),
                     instance_count: 1,
+                    overflowed: false,
+                    declared: None,
                 }),
                 array: None,
                 object: None,
+                object_count: 0,
+                variants: None,
+                candidates: Vec::new(),
             },
-            DataValues::Array(a) => Self {
-                types: None,
-                array: Some(Box::new(
-                    a.into_iter()
-                        .map(Self::from_data)
-                        .reduce(crunch_schemas)
-                        .unwrap_or(Self::new()),
-                )),
-                object: None,
-            },
-            DataValues::Object(o) => Self {
-                types: None,
-                array: None,
-                object: Some(
-                    o.into_iter()
-                        .map(|(k, v)| {
-                            (
-                                k,
-                                ObjectProperty {
-                                    value: Self::from_data(v),
-                                    required: true,
-                                },
-                            )
-                        })
-                        .collect(),
-                ),
-            },
+            DataValues::Array(a) => {
+                let lengths = LengthRange::new(a.len());
+
+                let unique = {
+                    let mut seen = HashSet::new();
+                    a.iter().all(|v| seen.insert(v))
+                };
+
+                let elements = a.into_iter().map(Self::from_data);
+
+                let (items, positions) = if lengths.max <= POSITIONS_MAX_LENGTH {
+                    (None, Some(elements.collect()))
+                } else {
+                    (elements.reduce(crunch_schemas), None)
+                };
+
+                Self {
+                    types: None,
+                    array: Some(SubschemaArray {
+                        items: Box::new(items.unwrap_or(Self::new())),
+                        positions,
+                        lengths,
+                        unique,
+                        instance_count: 1,
+                    }),
+                    object: None,
+                    object_count: 0,
+                    variants: None,
+                    candidates: Vec::new(),
+                }
+            }
+            DataValues::Object(o) => {
+                // Any key holding a string could tell the kinds of object apart
+                let candidates = o
+                    .iter()
+                    .filter_map(|(k, v)| match v {
+                        DataValues::Literal(
+                            Literals::String(s)
+                            | Literals::Formatted(_, s)
+                            | Literals::Temporal(_, s),
+                        ) => Some(Candidate {
+                            key: k.to_owned(),
+                            tags: Tags::Single(s.to_owned()),
+                            split: None,
+                        }),
+                        _ => None,
+                    })
+                    .collect();
+
+                let object = o
+                    .into_iter()
+                    .map(|(k, v)| {
+                        (
+                            k,
+                            ObjectProperty {
+                                value: Self::from_data(v),
+                                required: true,
+                            },
+                        )
+                    })
+                    .collect::<IndexMap<_, _>>();
+
+                Self {
+                    types: None,
+                    array: None,
+                    object: Some(object),
+                    object_count: 1,
+                    variants: None,
+                    candidates,
+                }
+            }
         }
     }
 }
 
-fn crunch_schemas(uo_1: Subschema, uo_2: Subschema) -> Subschema {
-    let types = match (uo_1.types, uo_2.types) {
-        (Some(s1), Some(s2)) => Some(SubschemaTypes {
-            values: {
-                let mut set = s1.values;
-                set.extend(s2.values);
-                set
-            },
-            instance_count: s1.instance_count + s2.instance_count,
+/// Schema of the objects carrying each value, as far as the merged objects of one side tell
+/// when it kept no split of its own. A side whose objects all carried one value is told exactly
+fn tag_schemas(shapes: &IndexMap<String, TagShape>, sb: &Subschema) -> IndexMap<String, Subschema> {
+    shapes
+        .iter()
+        .map(|(tag, shape)| {
+            let object = sb
+                .object
+                .iter()
+                .flatten()
+                .filter_map(|(k, p)| {
+                    shape.keys.get(k).map(|n| {
+                        let property = ObjectProperty {
+                            value: p.value.clone(),
+                            required: *n == shape.count,
+                        };
+
+                        (k.to_owned(), property)
+                    })
+                })
+                .collect();
+
+            let schema = Subschema {
+                object: Some(object),
+                object_count: shape.count,
+                ..Subschema::new()
+            };
+
+            (tag.to_owned(), schema)
+        })
+        .collect()
+}
+
+/// Merges the schemas of the objects carrying each value
+fn crunch_tags(
+    mut schemas: IndexMap<String, Subschema>,
+    other: IndexMap<String, Subschema>,
+) -> IndexMap<String, Subschema> {
+    for (tag, sub_2) in other {
+        match schemas.get_mut(&tag) {
+            Some(sub_1) => *sub_1 = crunch_schemas(mem::replace(sub_1, Subschema::new()), sub_2),
+            None => {
+                schemas.insert(tag, sub_2);
+            }
+        }
+    }
+
+    schemas
+}
+
+/// Keeps a discriminator observed on both sides, a side without objects has nothing to disagree
+/// with
+fn crunch_variants(sb_1: &mut Subschema, sb_2: &mut Subschema) -> Option<TaggedVariants> {
+    match (sb_1.variants.take(), sb_2.variants.take()) {
+        (Some(v1), Some(v2)) if v1.key == v2.key => Some(TaggedVariants {
+            values: crunch_tags(v1.values, v2.values),
+            key: v1.key,
         }),
+        (Some(v1), None) if sb_2.object.is_none() => Some(v1),
+        (None, Some(v2)) if sb_1.object.is_none() => Some(v2),
+        _ => None,
+    }
+}
+
+/// Keeps the candidates observed on both sides, as with the chosen discriminator. Objects are
+/// only copied per value while values come with different keys
+fn crunch_candidates(sb_1: &mut Subschema, sb_2: &mut Subschema) -> Vec<Candidate> {
+    let candidates_1 = mem::take(&mut sb_1.candidates);
+    let mut candidates_2 = mem::take(&mut sb_2.candidates);
+
+    if sb_1.object.is_none() {
+        return candidates_2;
+    } else if sb_2.object.is_none() {
+        return candidates_1;
+    }
+
+    candidates_1
+        .into_iter()
+        .filter_map(|c1| {
+            let i = candidates_2.iter().position(|c2| c2.key == c1.key)?;
+            let c2 = candidates_2.swap_remove(i);
+
+            if let (Tags::Single(t1), Tags::Single(t2)) = (&c1.tags, &c2.tags)
+                && t1 == t2
+            {
+                return Some(c1);
+            }
+
+            let mut shapes = c1.tags.into_shapes(sb_1);
+
+            // Values only come to differ in their keys once some value gains a key, which is
+            // when the shapes of the first side are needed apart
+            let mut grown = false;
+            c2.tags.each(sb_2, |tag, _, keys| {
+                grown |= shapes.get(tag).is_none_or(|s| s.lacks(keys))
+            });
+            let shapes_1 = (grown && c1.split.is_none()).then(|| shapes.clone());
+
+            c2.tags.each(sb_2, |tag, count, keys| {
+                add_shape(&mut shapes, tag, count, keys)
+            });
+
+            if shapes.len() > VARIANTS_MAX_COUNT {
+                return None;
+            }
+
+            let mut candidate = Candidate {
+                key: c1.key,
+                tags: Tags::Shapes(shapes),
+                split: None,
+            };
+
+            let splits = match grown {
+                true => candidate.splits(),
+                false => c1.split.is_some(),
+            };
+
+            if splits {
+                let split_1 = match c1.split {
+                    Some(split) => split,
+                    None => tag_schemas(&shapes_1.unwrap_or_default(), sb_1),
+                };
+                let split_2 = match c2.split {
+                    Some(split) => split,
+                    None => tag_schemas(&c2.tags.into_shapes(sb_2), sb_2),
+                };
+
+                candidate.split = Some(crunch_tags(split_1, split_2));
+            }
+
+            Some(candidate)
+        })
+        .collect()
+}
+
+pub fn crunch_schemas(mut uo_1: Subschema, mut uo_2: Subschema) -> Subschema {
+    let variants = crunch_variants(&mut uo_1, &mut uo_2);
+    let candidates = crunch_candidates(&mut uo_1, &mut uo_2);
+
+    let types = match (uo_1.types, uo_2.types) {
+        (Some(s1), Some(s2)) => Some(s1.merge(s2)),
         (Some(s1), None) => Some(s1),
         (None, Some(s2)) => Some(s2),
         (None, None) => None,
     };
 
     let array = match (uo_1.array, uo_2.array) {
-        (Some(s1), Some(s2)) => Some(Box::new(crunch_schemas(*s1, *s2))),
+        (Some(s1), Some(s2)) => {
+            let lengths = s1.lengths.merge(s2.lengths);
+            let unique = s1.unique && s2.unique;
+            let instance_count = s1.instance_count + s2.instance_count;
+
+            // Positions only line up while both sides share one length
+            let (items, positions) = match (&s1.positions, &s2.positions) {
+                (Some(_), Some(_)) if s1.lengths == s2.lengths => (
+                    Subschema::new(),
+                    s1.positions.zip(s2.positions).map(|(p1, p2)| {
+                        p1.into_iter()
+                            .zip(p2)
+                            .map(|(p1, p2)| crunch_schemas(p1, p2))
+                            .collect()
+                    }),
+                ),
+                _ => (crunch_schemas(s1.into_items(), s2.into_items()), None),
+            };
+
+            Some(SubschemaArray {
+                items: Box::new(items),
+                positions,
+                lengths,
+                unique,
+                instance_count,
+            })
+        }
         (Some(s1), None) => Some(s1),
         (None, Some(s2)) => Some(s2),
         (None, None) => None,
@@ -94,7 +711,7 @@ fn crunch_schemas(uo_1: Subschema, uo_2: Subschema) -> Subschema {
 
     let object = match (uo_1.object, uo_2.object) {
         (Some(s1), Some(mut s2)) => {
-            let mut new_map: HashMap<String, ObjectProperty> = HashMap::new();
+            let mut new_map: IndexMap<String, ObjectProperty> = IndexMap::new();
 
             for (
                 key,
@@ -107,7 +724,7 @@ fn crunch_schemas(uo_1: Subschema, uo_2: Subschema) -> Subschema {
                 let Some(ObjectProperty {
                     value: sub_2,
                     required: req_2,
-                }) = s2.remove(&key)
+                }) = s2.shift_remove(&key)
                 else {
                     // If key not in other object, insert self with required false
                     new_map.insert(
@@ -161,6 +778,9 @@ fn crunch_schemas(uo_1: Subschema, uo_2: Subschema) -> Subschema {
         types,
         array,
         object,
+        object_count: uo_1.object_count + uo_2.object_count,
+        variants,
+        candidates,
     }
 }
 
@@ -169,8 +789,11 @@ fn crunch_schemas(uo_1: Subschema, uo_2: Subschema) -> Subschema {
 mod tests {
     use std::collections::HashSet;
 
-    use super::super::{DataValues, Literals};
-    use super::{ObjectProperty, Subschema, SubschemaTypes, crunch_schemas};
+    use super::super::{DataValues, Literals, StringFormat};
+    use super::{
+        LengthRange, ObjectProperty, POSITIONS_MAX_LENGTH, Subschema, SubschemaArray,
+        SubschemaTypes, TaggedVariants, Tags, VALUES_MAX_COUNT, VARIANTS_MAX_COUNT, crunch_schemas,
+    };
 
     fn nul() -> DataValues {
         DataValues::Literal(Literals::Null)
@@ -208,7 +831,10 @@ mod tests {
             Subschema {
                 types: Some(SubschemaTypes {
                     values: HashSet::from([Literals::Null]),
-                    instance_count: 1
+                    instance_count: 1,
+                    string_lengths: None,
+                    overflowed: false,
+                    declared: None
                 }),
                 ..Subschema::new()
             }
@@ -217,15 +843,334 @@ mod tests {
         assert_eq!(
             Subschema::from_data(arr(&[bol(true), bol(false)])),
             Subschema {
-                array: Some(Box::new(Subschema {
-                    types: Some(SubschemaTypes {
-                        values: HashSet::from([Literals::Boolean(true), Literals::Boolean(false)]),
-                        instance_count: 2
-                    }),
-                    ..Subschema::new()
-                })),
+                array: Some(SubschemaArray {
+                    items: Box::new(Subschema::new()),
+                    positions: Some(vec![
+                        Subschema::from_data(bol(true)),
+                        Subschema::from_data(bol(false))
+                    ]),
+                    lengths: LengthRange::new(2),
+                    unique: true,
+                    instance_count: 1
+                }),
+                ..Subschema::new()
+            }
+        );
+
+        assert_eq!(
+            Subschema::from_data(arr(&[bol(true), bol(false)]))
+                .array
+                .unwrap()
+                .into_items(),
+            Subschema {
+                types: Some(SubschemaTypes {
+                    values: HashSet::from([Literals::Boolean(true), Literals::Boolean(false)]),
+                    instance_count: 2,
+                    string_lengths: None,
+                    overflowed: false,
+                    declared: None
+                }),
+                ..Subschema::new()
+            }
+        );
+    }
+
+    fn str(s: &str) -> DataValues {
+        DataValues::Literal(Literals::String(s.to_owned()))
+    }
+
+    #[test]
+    fn string_lengths() {
+        let lengths = |data| {
+            Subschema::from_data(data)
+                .types
+                .and_then(|t| t.string_lengths)
+        };
+
+        assert_eq!(lengths(int(1)), None);
+        assert_eq!(lengths(str("")), Some(LengthRange { min: 0, max: 0 }));
+
+        let Some(array) =
+            Subschema::from_data(arr(&[str("ab"), int(12345), str("åäö!"), nul()])).array
+        else {
+            panic!("Array schema expected");
+        };
+        assert_eq!(
+            array.into_items().types.and_then(|t| t.string_lengths),
+            Some(LengthRange { min: 2, max: 4 })
+        );
+    }
+
+    #[test]
+    fn declared() {
+        let mut sb = Subschema::from_data(obj(&[(
+            "users",
+            arr(&[obj(&[("id", int(1))]), obj(&[("id", int(2)), ("x", nul())])]),
+        )]));
+
+        sb.declare(&[String::from("users"), String::from("id")], "INTEGER");
+        sb.declare(&[String::from("users"), String::from("missing")], "TEXT");
+
+        let users = sb.object.unwrap().shift_remove("users").unwrap().value;
+        let items = users.array.unwrap().into_items().object.unwrap();
+
+        assert_eq!(
+            items["id"]
+                .value
+                .types
+                .as_ref()
+                .unwrap()
+                .declared
+                .as_deref(),
+            Some("INTEGER")
+        );
+        assert_eq!(items["x"].value.types.as_ref().unwrap().declared, None);
+    }
+
+    #[test]
+    fn array_cardinality() {
+        let array = |data| Subschema::from_data(data).array.unwrap();
+
+        let empty = array(arr(&[]));
+        assert_eq!(empty.lengths, LengthRange::new(0));
+        assert!(empty.unique);
+
+        let nested = array(arr(&[
+            arr(&[int(1), int(2), int(3)]),
+            arr(&[obj(&[("a", int(1))]), obj(&[("a", int(2))])]),
+        ]));
+        assert_eq!(nested.lengths, LengthRange::new(2));
+        assert!(nested.unique);
+
+        let inner = nested.into_items().array.unwrap();
+        assert_eq!(inner.lengths, LengthRange { min: 2, max: 3 });
+        assert!(inner.unique);
+
+        let duplicated = array(arr(&[
+            arr(&[int(1), int(2)]),
+            arr(&[
+                obj(&[("a", int(1)), ("b", nul())]),
+                obj(&[("b", nul()), ("a", int(1))]),
+            ]),
+        ]));
+        assert!(!duplicated.into_items().array.unwrap().unique);
+
+        // Numbers are compared by value, whatever they were written as
+        assert!(!array(arr(&[int(1), flt(1.0)])).unique);
+        assert!(!array(arr(&[obj(&[("a", flt(-0.0))]), obj(&[("a", int(0))])])).unique);
+        assert!(array(arr(&[int(1), flt(1.5)])).unique);
+    }
+
+    #[test]
+    fn array_positions() {
+        let array = |data| Subschema::from_data(data).array.unwrap();
+
+        let pairs = array(arr(&[
+            arr(&[int(1), str("a")]),
+            arr(&[int(2), nul()]),
+            arr(&[int(3), str("c")]),
+        ]));
+        let inner = pairs.into_items().array.unwrap();
+        assert_eq!(inner.instance_count, 3);
+        assert_eq!(*inner.items, Subschema::new());
+        assert_eq!(
+            inner.positions,
+            Some(vec![
+                array(arr(&[int(1), int(2), int(3)])).into_items(),
+                array(arr(&[str("a"), nul(), str("c")])).into_items(),
+            ])
+        );
+
+        let ragged = array(arr(&[arr(&[int(1), str("a")]), arr(&[int(2)])]));
+        let ragged = ragged.into_items().array.unwrap();
+        assert_eq!(ragged.positions, None);
+        assert_eq!(
+            *ragged.items,
+            array(arr(&[int(1), str("a"), int(2)])).into_items()
+        );
+
+        let long = array(arr(&vec![int(1); POSITIONS_MAX_LENGTH + 1]));
+        assert_eq!(long.positions, None);
+    }
+
+    #[test]
+    fn variants() {
+        let click = obj(&[("type", str("click")), ("x", int(1)), ("y", int(2))]);
+        let key = obj(&[("type", str("key")), ("code", int(65))]);
+        let items = |data| {
+            let mut items = Subschema::from_data(data).array.unwrap().into_items();
+            items.choose_variants();
+            items
+        };
+
+        let variants = items(arr(&[click.clone(), key.clone(), click.clone()]))
+            .variants
+            .unwrap();
+        assert_eq!(variants.key, "type");
+
+        let values = variants.values;
+        assert_eq!(values.keys().collect::<Vec<_>>(), ["click", "key"]);
+        assert_eq!(values["click"], items(arr(&[click.clone(), click.clone()])));
+        assert_eq!(
+            values["key"],
+            Subschema {
+                candidates: Vec::new(),
+                ..Subschema::from_data(key.clone())
+            }
+        );
+
+        // Objects are only copied per value once values come with different keys
+        let uniform = |t| obj(&[("type", str(t)), ("x", int(1))]);
+        let candidate = |data| {
+            let items = Subschema::from_data(data).array.unwrap().into_items();
+            items.candidates[0].clone()
+        };
+
+        let single = candidate(arr(&[uniform("a"), uniform("a")]));
+        assert_eq!(single.tags, Tags::Single(String::from("a")));
+
+        let shaped = candidate(arr(&[uniform("a"), uniform("b")]));
+        let Tags::Shapes(shapes) = shaped.tags else {
+            panic!("Expected the keys of each value");
+        };
+        assert_eq!(shapes.keys().collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(shaped.split, None);
+
+        // Tags with a format are still strings
+        let email = DataValues::Literal(Literals::Formatted(
+            StringFormat::Email,
+            String::from("a@example.com"),
+        ));
+        assert_eq!(
+            Subschema::from_data(obj(&[("kind", email)])).candidates[0].key,
+            "kind"
+        );
+
+        // Literals alongside the objects don't disturb discriminators
+        assert!(
+            items(arr(&[click.clone(), nul(), key.clone(), click.clone()]))
+                .variants
+                .is_some()
+        );
+
+        // Every object needs the same string discriminator
+        for other in [
+            obj(&[("x", int(1))]),
+            obj(&[("type", int(1))]),
+            obj(&[("kind", str("click"))]),
+        ] {
+            assert_eq!(
+                items(arr(&[click.clone(), other, key.clone(), click.clone()])).variants,
+                None
+            );
+        }
+
+        // Any key can discriminate, the one whose values line up with the keys objects come with
+        // wins over a conventional name that doesn't
+        let open = obj(&[
+            ("action", str("open")),
+            ("type", str("ok")),
+            ("path", str("a")),
+        ]);
+        let close = |t| obj(&[("action", str("close")), ("type", str(t)), ("code", int(0))]);
+        assert_eq!(
+            items(arr(&[open.clone(), close("ok"), close("failed"), open]))
+                .variants
+                .unwrap()
+                .key,
+            "action"
+        );
+
+        // Conventional names only break ties
+        let a = obj(&[("group", str("a")), ("kind", str("a")), ("x", int(1))]);
+        let b = obj(&[("group", str("b")), ("kind", str("b")), ("y", int(1))]);
+        assert_eq!(items(arr(&[a.clone(), b, a])).variants.unwrap().key, "kind");
+
+        // Values carried once each split nothing
+        let ids = [
+            obj(&[("id", str("a")), ("x", int(1))]),
+            obj(&[("id", str("b")), ("y", int(1))]),
+        ];
+        assert_eq!(items(arr(&ids)).variants, None);
+
+        let many = (0..=VARIANTS_MAX_COUNT)
+            .map(|i| obj(&[("type", str(&i.to_string()))]))
+            .collect::<Vec<_>>();
+        assert_eq!(items(arr(&many)).variants, None);
+    }
+
+    #[test]
+    fn bounded_values() {
+        let records = (0..VALUES_MAX_COUNT as i64 * 4)
+            .flat_map(|i| [int(i), str(&i.to_string()), nul()])
+            .map(Ok::<_, ()>);
+
+        let types = Subschema::from_records(records)
+            .unwrap()
+            .array
+            .unwrap()
+            .into_items()
+            .types
+            .unwrap();
+
+        assert!(types.overflowed);
+        assert_eq!(types.instance_count, VALUES_MAX_COUNT * 12);
+        assert_eq!(types.values.len(), 4);
+        assert!(types.values.contains(&Literals::Null));
+        assert!(types.values.contains(&Literals::Integer(0)));
+        assert!(
+            types
+                .values
+                .contains(&Literals::Integer(VALUES_MAX_COUNT as i64 * 4 - 1))
+        );
+
+        let few = Subschema::from_data(arr(&[int(1), int(2), int(3)]))
+            .array
+            .unwrap()
+            .into_items()
+            .types
+            .unwrap();
+        assert!(!few.overflowed);
+        assert_eq!(few.values.len(), 3);
+    }
+
+    #[test]
+    fn records() {
+        let records = [
+            obj(&[("a", int(1)), ("b", bol(true))]),
+            obj(&[("a", flt(1.5))]),
+            obj(&[("a", nul()), ("c", arr(&[int(2)]))]),
+        ];
+
+        // Streamed records are never checked for uniqueness or positions
+        let not_unique = |data| {
+            let array = Subschema::from_data(data).array.unwrap();
+
+            Subschema {
+                array: Some(SubschemaArray {
+                    lengths: array.lengths,
+                    unique: false,
+                    instance_count: array.instance_count,
+                    positions: None,
+                    items: Box::new(array.into_items()),
+                }),
                 ..Subschema::new()
             }
+        };
+
+        assert_eq!(
+            Subschema::from_records(records.clone().map(Ok::<_, ()>)),
+            Ok(not_unique(arr(&records)))
+        );
+
+        assert_eq!(
+            Subschema::from_records(Vec::<Result<DataValues, ()>>::new()),
+            Ok(not_unique(arr(&[])))
+        );
+
+        assert_eq!(
+            Subschema::from_records([Ok(int(1)), Err("bad record"), Ok(int(2))]),
+            Err("bad record")
         );
     }
 